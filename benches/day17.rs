@@ -0,0 +1,31 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+
+use adventofcode2024::day17;
+
+/// A program like the puzzle inputs, which outputs 3 bits of A at a time
+const PROGRAM: [usize; 16] = [2, 4, 1, 5, 7, 5, 1, 6, 0, 3, 4, 0, 5, 5, 3, 0];
+
+/// Run the program for many values of A, like a brute force search for part 2
+fn search<E>(execute: impl Fn(&mut day17::Registers) -> Result<Vec<usize>, E>) -> usize {
+    (0..1000usize)
+        .map(|a| execute(&mut [a << 30, 0, 0]).ok().unwrap().len())
+        .sum()
+}
+
+fn bench_vm(c: &mut Criterion) {
+    let program = PROGRAM.to_vec();
+    let compiled = day17::compile(&program);
+
+    let mut group = c.benchmark_group("day17_execute");
+    group.bench_function("interpreter", |b| {
+        b.iter(|| search(|registers| day17::execute_program(black_box(&program), registers)))
+    });
+    group.bench_function("compiled", |b| {
+        b.iter(|| search(|registers| black_box(&compiled).execute(registers)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_vm);
+criterion_main!(benches);
@@ -0,0 +1,333 @@
+use adventofcode2024::*;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+// The examples of the puzzles, shared with the tests of each day
+
+const DAY01: &str = include_str!("../fixtures/day01.txt");
+const DAY02: &str = include_str!("../fixtures/day02.txt");
+const DAY03: &str = include_str!("../fixtures/day03.txt");
+const DAY03_P2: &str = include_str!("../fixtures/day03_p2.txt");
+const DAY04: &str = include_str!("../fixtures/day04.txt");
+const DAY05: &str = include_str!("../fixtures/day05.txt");
+const DAY06: &str = include_str!("../fixtures/day06.txt");
+const DAY07: &str = include_str!("../fixtures/day07.txt");
+const DAY08: &str = include_str!("../fixtures/day08.txt");
+const DAY09: &str = include_str!("../fixtures/day09.txt");
+const DAY10: &str = include_str!("../fixtures/day10.txt");
+const DAY11: &str = include_str!("../fixtures/day11.txt");
+const DAY12: &str = include_str!("../fixtures/day12.txt");
+const DAY13: &str = include_str!("../fixtures/day13.txt");
+const DAY14: &str = include_str!("../fixtures/day14.txt");
+const DAY15: &str = include_str!("../fixtures/day15.txt");
+const DAY16: &str = include_str!("../fixtures/day16.txt");
+const DAY17: &str = include_str!("../fixtures/day17.txt");
+const DAY17_P2: &str = include_str!("../fixtures/day17_p2.txt");
+const DAY18: &str = include_str!("../fixtures/day18.txt");
+const DAY19: &str = include_str!("../fixtures/day19.txt");
+const DAY20: &str = include_str!("../fixtures/day20.txt");
+const DAY21: &str = include_str!("../fixtures/day21.txt");
+const DAY22: &str = include_str!("../fixtures/day22.txt");
+const DAY23: &str = include_str!("../fixtures/day23.txt");
+const DAY24: &str = include_str!("../fixtures/day24.txt");
+const DAY24_LARGE: &str = include_str!("../fixtures/day24_large.txt");
+const DAY25: &str = include_str!("../fixtures/day25.txt");
+
+/// Time `solver` alone, on a fresh input from `setup` for each iteration
+fn bench_part<I, O>(c: &mut Criterion, name: &str, setup: impl Fn() -> I, solver: impl Fn(I) -> O) {
+    c.bench_function(name, |b| {
+        b.iter_batched(&setup, &solver, BatchSize::SmallInput)
+    });
+}
+
+fn days_01_to_10(c: &mut Criterion) {
+    bench_part(
+        c,
+        "day01::part1",
+        || day01::parse_input(DAY01).unwrap(),
+        day01::part1,
+    );
+    bench_part(
+        c,
+        "day01::part2",
+        || day01::parse_input(DAY01).unwrap(),
+        day01::part2,
+    );
+    bench_part(
+        c,
+        "day02::part1",
+        || day02::parse_input(DAY02).unwrap(),
+        day02::part1,
+    );
+    bench_part(
+        c,
+        "day02::part2",
+        || day02::parse_input(DAY02).unwrap(),
+        day02::part2,
+    );
+    bench_part(
+        c,
+        "day03::part1",
+        || day03::parse_input(DAY03).unwrap(),
+        day03::part1,
+    );
+    bench_part(
+        c,
+        "day03::part2",
+        || day03::parse_input_p2(DAY03_P2).unwrap(),
+        day03::part1,
+    );
+    bench_part(
+        c,
+        "day04::part1",
+        || day04::parse_input(DAY04).unwrap(),
+        day04::part1,
+    );
+    bench_part(
+        c,
+        "day04::part2",
+        || day04::parse_input_p2(DAY04).unwrap(),
+        day04::part2,
+    );
+    bench_part(
+        c,
+        "day05::part1",
+        || day05::parse_input(DAY05).unwrap(),
+        day05::part1,
+    );
+    bench_part(
+        c,
+        "day05::part2",
+        || day05::parse_input(DAY05).unwrap(),
+        day05::part2,
+    );
+    bench_part(
+        c,
+        "day06::part1",
+        || day06::parse_input(DAY06).unwrap(),
+        day06::part1,
+    );
+    bench_part(
+        c,
+        "day06::part2",
+        || day06::parse_input(DAY06).unwrap(),
+        day06::part2,
+    );
+    bench_part(
+        c,
+        "day07::part1",
+        || day07::parse_input(DAY07).unwrap(),
+        day07::part1,
+    );
+    bench_part(
+        c,
+        "day07::part2",
+        || day07::parse_input(DAY07).unwrap(),
+        day07::part2,
+    );
+    bench_part(
+        c,
+        "day08::part1",
+        || day08::parse_input(DAY08).unwrap(),
+        day08::part1,
+    );
+    bench_part(
+        c,
+        "day08::part2",
+        || day08::parse_input(DAY08).unwrap(),
+        day08::part2,
+    );
+    bench_part(
+        c,
+        "day09::part1",
+        || day09::parse_input(DAY09).unwrap(),
+        day09::part1,
+    );
+    bench_part(
+        c,
+        "day09::part2",
+        || day09::parse_input(DAY09).unwrap(),
+        day09::part2,
+    );
+    bench_part(
+        c,
+        "day10::part1",
+        || day10::parse_input(DAY10).unwrap(),
+        day10::part1,
+    );
+    bench_part(
+        c,
+        "day10::part2",
+        || day10::parse_input(DAY10).unwrap(),
+        day10::part2,
+    );
+}
+
+fn days_11_to_20(c: &mut Criterion) {
+    bench_part(
+        c,
+        "day11::part1",
+        || (day11::parse_input(DAY11).unwrap(), day11::Params::default()),
+        day11::part1,
+    );
+    bench_part(
+        c,
+        "day11::part2",
+        || (day11::parse_input(DAY11).unwrap(), day11::Params::default()),
+        day11::part2,
+    );
+    bench_part(
+        c,
+        "day12::part1",
+        || day12::parse_input(DAY12).unwrap(),
+        day12::part1,
+    );
+    bench_part(
+        c,
+        "day12::part2",
+        || day12::parse_input(DAY12).unwrap(),
+        day12::part2,
+    );
+    bench_part(
+        c,
+        "day13::part1",
+        || day13::parse_input(DAY13).unwrap(),
+        day13::part1,
+    );
+    bench_part(
+        c,
+        "day13::part2",
+        || day13::parse_input(DAY13).unwrap(),
+        day13::part2,
+    );
+    let day14 = || {
+        let robots = day14::parse_input(DAY14).unwrap();
+        let params = day14::Params {
+            width: 11,
+            height: 7,
+            ..Default::default()
+        };
+        (robots, params)
+    };
+    bench_part(c, "day14::part1", day14, day14::part1);
+    bench_part(c, "day14::part2", day14, day14::part2);
+    bench_part(
+        c,
+        "day15::part1",
+        || day15::parse_input(DAY15).unwrap(),
+        day15::part1,
+    );
+    bench_part(
+        c,
+        "day15::part2",
+        || day15::parse_input_p2(DAY15).unwrap(),
+        day15::part2,
+    );
+    bench_part(
+        c,
+        "day16::part1",
+        || (day16::parse_input(DAY16).unwrap(), day16::Params::default()),
+        day16::part1,
+    );
+    bench_part(
+        c,
+        "day16::part2",
+        || (day16::parse_input(DAY16).unwrap(), day16::Params::default()),
+        day16::part2,
+    );
+    bench_part(
+        c,
+        "day17::part1",
+        || day17::parse_input(DAY17).unwrap(),
+        day17::part1,
+    );
+    bench_part(
+        c,
+        "day17::part2",
+        || day17::parse_input(DAY17_P2).unwrap(),
+        day17::part2,
+    );
+    let day18 = || {
+        let params = day18::Params { size: 7, bytes: 12 };
+        (day18::parse_input(DAY18, &params).unwrap(), params)
+    };
+    bench_part(c, "day18::part1", day18, day18::part1);
+    bench_part(c, "day18::part2", day18, day18::part2);
+    bench_part(
+        c,
+        "day19::part1",
+        || day19::parse_input(DAY19).unwrap(),
+        day19::part1,
+    );
+    bench_part(
+        c,
+        "day19::part2",
+        || day19::parse_input(DAY19).unwrap(),
+        day19::part2,
+    );
+    let day20 = || {
+        let maze = day20::parse_input(DAY20).unwrap();
+        (maze, day20::Params { min_saving: 50 })
+    };
+    bench_part(c, "day20::part1", day20, day20::part1);
+    bench_part(c, "day20::part2", day20, day20::part2);
+}
+
+fn days_21_to_25(c: &mut Criterion) {
+    bench_part(
+        c,
+        "day21::part1",
+        || day21::parse_input(DAY21).unwrap(),
+        day21::part1,
+    );
+    bench_part(
+        c,
+        "day21::part2",
+        || day21::parse_input(DAY21).unwrap(),
+        day21::part2,
+    );
+    bench_part(
+        c,
+        "day22::part1",
+        || day22::parse_input(DAY22).unwrap(),
+        day22::part1,
+    );
+    bench_part(
+        c,
+        "day22::part2",
+        || day22::parse_input(DAY22).unwrap(),
+        day22::part2,
+    );
+    bench_part(
+        c,
+        "day23::part1",
+        || day23::parse_input(DAY23).unwrap(),
+        day23::part1,
+    );
+    bench_part(
+        c,
+        "day23::part2",
+        || day23::parse_input(DAY23).unwrap(),
+        day23::part2,
+    );
+    bench_part(
+        c,
+        "day24::part1",
+        || day24::parse_input(DAY24).unwrap(),
+        day24::part1,
+    );
+    bench_part(
+        c,
+        "day24::part2",
+        || day24::parse_input(DAY24_LARGE).unwrap(),
+        day24::part2,
+    );
+    bench_part(
+        c,
+        "day25::part1",
+        || day25::parse_input(DAY25).unwrap(),
+        day25::part1,
+    );
+}
+
+criterion_group!(benches, days_01_to_10, days_11_to_20, days_21_to_25);
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::hint::black_box;
+
+use adventofcode2024::grid::{DenseGrid, Grid};
+use adventofcode2024::{day06, day16};
+
+// The examples of the puzzles, shared with the tests of each day
+
+const DAY06: &str = include_str!("../fixtures/day06.txt");
+const DAY16: &str = include_str!("../fixtures/day16.txt");
+
+/// The storage alone: a lookup of every cell and a clone, on the same map
+fn bench_grids(c: &mut Criterion) {
+    let hashed = Grid::parse(DAY06, |_, c| c == '#');
+    let dense = DenseGrid::parse(DAY06, |_, c| c == '#');
+
+    let mut group = c.benchmark_group("lookup");
+    group.bench_function("hashmap", |b| {
+        b.iter(|| {
+            let grid = black_box(&hashed);
+            grid.positions().filter(|pos| grid[pos]).count()
+        })
+    });
+    group.bench_function("dense", |b| {
+        b.iter(|| {
+            let grid = black_box(&dense);
+            grid.positions().filter(|pos| grid[pos]).count()
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("clone");
+    group.bench_function("hashmap", |b| b.iter(|| black_box(&hashed).clone()));
+    group.bench_function("dense", |b| b.iter(|| black_box(&dense).clone()));
+    group.finish();
+}
+
+/// The days that moved to [`DenseGrid`], solved on their examples
+fn bench_days(c: &mut Criterion) {
+    let day06 = || day06::parse_input(DAY06).unwrap();
+    c.bench_function("day06::part1", |b| {
+        b.iter_batched(day06, day06::part1, BatchSize::SmallInput)
+    });
+    c.bench_function("day06::part2", |b| {
+        b.iter_batched(day06, day06::part2, BatchSize::SmallInput)
+    });
+
+    let day16 = || (day16::parse_input(DAY16).unwrap(), day16::Params::default());
+    c.bench_function("day16::part1", |b| {
+        b.iter_batched(day16, day16::part1, BatchSize::SmallInput)
+    });
+    c.bench_function("day16::part2", |b| {
+        b.iter_batched(day16, day16::part2, BatchSize::SmallInput)
+    });
+}
+
+criterion_group!(benches, bench_grids, bench_days);
+criterion_main!(benches);
@@ -0,0 +1,466 @@
+use std::fmt::Display;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use aoc_next::parser::Parsing;
+use aoc_next::solution::Solver;
+use aoc_next::{FailableParser, Solution};
+use itertools::Itertools;
+
+use crate::params::{Override, Params};
+
+/// The min, median and max of several durations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timings {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Timings {
+    pub fn new(mut durations: Vec<Duration>) -> Self {
+        assert!(!durations.is_empty(), "no durations to summarize");
+        durations.sort();
+        Timings {
+            min: durations[0],
+            median: durations[durations.len() / 2],
+            max: durations[durations.len() - 1],
+        }
+    }
+}
+
+impl Display for Timings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "min {:?}, median {:?}, max {:?}",
+            self.min, self.median, self.max
+        )
+    }
+}
+
+/// The timings of `runs` runs of the parser and of the solver of a solution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measure {
+    pub day: u8,
+    pub parser: &'static str,
+    pub solver: &'static str,
+    pub runs: usize,
+    pub parse: Timings,
+    pub solve: Timings,
+    /// The answer of the last run
+    pub answer: String,
+}
+
+impl Display for Measure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Day {} - {} with {} ({} runs)",
+            self.day, self.solver, self.parser, self.runs
+        )?;
+        writeln!(f, "\tparser: {}", self.parse)?;
+        write!(f, "\tsolver: {}", self.solve)
+    }
+}
+
+/// A solution that can be run and timed on its own, apart from downloading the input
+pub trait Bench {
+    fn day(&self) -> u8;
+
+    /// Run the parser then the solver `runs` times on `input`, with `overrides` applied to
+    /// the parameters of the day
+    fn bench(&self, input: &str, runs: usize, overrides: &[Override]) -> Result<Measure>;
+}
+
+/// A parser that can check the input against the [`Params`] of its day
+pub trait ParsingWith<I, Q> {
+    fn run(&self, input: &str, params: &Q) -> Result<I>;
+    fn name(&self) -> &'static str;
+}
+
+/// The parsers that do not need the parameters ignore them
+impl<F, I, Q> ParsingWith<I, Q> for FailableParser<F, I>
+where
+    F: Fn(&str) -> Result<I>,
+{
+    fn run(&self, input: &str, _params: &Q) -> Result<I> {
+        (self.run)(input)
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+/// A parser that takes the parameters of its day. Declared with
+/// [`params_parser!`](crate::params_parser).
+pub struct ParamsParser<F> {
+    pub run: F,
+    pub name: &'static str,
+}
+
+impl<F, I, Q> ParsingWith<I, Q> for ParamsParser<F>
+where
+    F: Fn(&str, &Q) -> Result<I>,
+{
+    fn run(&self, input: &str, params: &Q) -> Result<I> {
+        (self.run)(input, params)
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+/// Declare a [`ParamsParser`], the same way as with [`aoc_next::failable_parser!`]
+#[macro_export]
+macro_rules! params_parser {
+    ($parser:expr) => {{
+        $crate::bench::ParamsParser {
+            run: $parser,
+            name: stringify!($parser),
+        }
+    }};
+}
+
+/// A solution whose parser and solver also take the [`Params`] of its day, resolved from the
+/// overrides given to [`Bench::bench`]. Declared with [`tuned!`](crate::tuned).
+pub struct Tuned<P, S, I, Q, O>
+where
+    P: ParsingWith<I, Q>,
+    S: Fn((I, Q)) -> O,
+    Q: Params + Copy,
+    O: Display,
+{
+    pub day: u8,
+    pub parser: P,
+    pub solver: Solver<S, (I, Q), O>,
+}
+
+/// Declare a [`Tuned`] solution, the same way as with [`aoc_next::solution!`]
+#[macro_export]
+macro_rules! tuned {
+    ($day:expr, $parser:expr, $solver:expr) => {{
+        &$crate::bench::Tuned {
+            day: $day,
+            parser: $parser,
+            solver: $solver,
+        }
+    }};
+}
+
+/// Time `runs` runs of `parse` and of `solve` on `input`
+fn time_runs<I, O: Display>(
+    (day, parser, solver): (u8, &'static str, &'static str),
+    input: &str,
+    runs: usize,
+    parse: impl Fn(&str) -> Result<I>,
+    solve: impl Fn(I) -> O,
+) -> Result<Measure> {
+    if runs == 0 {
+        bail!("at least one run is needed");
+    }
+    let mut parse_durations = Vec::with_capacity(runs);
+    let mut solve_durations = Vec::with_capacity(runs);
+    let mut answer = None;
+    for _ in 0..runs {
+        let start = Instant::now();
+        let parsed = parse(black_box(input))?;
+        parse_durations.push(start.elapsed());
+        let start = Instant::now();
+        let output = black_box(solve(parsed));
+        solve_durations.push(start.elapsed());
+        answer = Some(output);
+    }
+    Ok(Measure {
+        day,
+        parser,
+        solver,
+        runs,
+        parse: Timings::new(parse_durations),
+        solve: Timings::new(solve_durations),
+        answer: answer.map(|answer| answer.to_string()).unwrap_or_default(),
+    })
+}
+
+impl<P, S, I, O> Bench for Solution<P, S, I, O>
+where
+    P: Parsing<I>,
+    S: Fn(I) -> O,
+    O: Display,
+{
+    fn day(&self) -> u8 {
+        self.day
+    }
+
+    fn bench(&self, input: &str, runs: usize, _overrides: &[Override]) -> Result<Measure> {
+        let names = (self.day, self.parser.name(), self.solver.name);
+        time_runs(
+            names,
+            input,
+            runs,
+            |input| self.parser.run(input),
+            |parsed| (self.solver.run)(parsed),
+        )
+    }
+}
+
+impl<P, S, I, Q, O> Bench for Tuned<P, S, I, Q, O>
+where
+    P: ParsingWith<I, Q>,
+    S: Fn((I, Q)) -> O,
+    Q: Params + Copy,
+    O: Display,
+{
+    fn day(&self) -> u8 {
+        self.day
+    }
+
+    fn bench(&self, input: &str, runs: usize, overrides: &[Override]) -> Result<Measure> {
+        let params = Q::with_overrides(overrides)?;
+        let names = (self.day, self.parser.name(), self.solver.name);
+        time_runs(
+            names,
+            input,
+            runs,
+            |input| self.parser.run(input, &params),
+            |parsed| (self.solver.run)((parsed, params)),
+        )
+    }
+}
+
+const COLUMNS: [&str; 10] = [
+    "day",
+    "parser",
+    "solver",
+    "runs",
+    "parse_min_ns",
+    "parse_median_ns",
+    "parse_max_ns",
+    "solve_min_ns",
+    "solve_median_ns",
+    "solve_max_ns",
+];
+
+fn values(measure: &Measure) -> [String; 10] {
+    [
+        measure.day.to_string(),
+        measure.parser.to_string(),
+        measure.solver.to_string(),
+        measure.runs.to_string(),
+        measure.parse.min.as_nanos().to_string(),
+        measure.parse.median.as_nanos().to_string(),
+        measure.parse.max.as_nanos().to_string(),
+        measure.solve.min.as_nanos().to_string(),
+        measure.solve.median.as_nanos().to_string(),
+        measure.solve.max.as_nanos().to_string(),
+    ]
+}
+
+/// `field` quoted if it holds a quote, a comma or a line break, with its quotes doubled
+fn csv_field(field: &str) -> String {
+    if field.contains(['"', ',', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// `value` as a JSON string, with its quotes, backslashes and control characters escaped
+fn json_string(value: &str) -> String {
+    let mut json = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => json += "\\\"",
+            '\\' => json += "\\\\",
+            '\n' => json += "\\n",
+            '\r' => json += "\\r",
+            '\t' => json += "\\t",
+            c if c.is_control() => json += &format!("\\u{:04x}", c as u32),
+            c => json.push(c),
+        }
+    }
+    json + "\""
+}
+
+/// One line per measure, with the durations in nanoseconds
+pub fn to_csv(measures: &[Measure]) -> String {
+    let mut csv = COLUMNS.join(",") + "\n";
+    for measure in measures {
+        csv += &values(measure).iter().map(|v| csv_field(v)).join(",");
+        csv += "\n";
+    }
+    csv
+}
+
+/// An array with one object per measure, with the durations in nanoseconds
+pub fn to_json(measures: &[Measure]) -> String {
+    let objects = measures.iter().map(|measure| {
+        let fields = COLUMNS
+            .iter()
+            .zip(values(measure))
+            .map(|(column, value)| match *column {
+                "parser" | "solver" => format!("\"{column}\": {}", json_string(&value)),
+                _ => format!("\"{column}\": {value}"),
+            })
+            .join(", ");
+        format!("  {{{fields}}}")
+    });
+    format!("[\n{}\n]\n", objects.format(",\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc_next::{failable_parser, solution, solver};
+    use pretty_assertions::assert_eq;
+
+    fn parse(input: &str) -> Result<usize> {
+        Ok(input.trim().parse()?)
+    }
+
+    fn double(value: usize) -> usize {
+        value * 2
+    }
+
+    fn parse_narrow(input: &str, params: &Width) -> Result<usize> {
+        let value = parse(input)?;
+        if value > params.width {
+            bail!("{value} is wider than {}", params.width);
+        }
+        Ok(value)
+    }
+
+    fn scale((value, params): (usize, Width)) -> usize {
+        value * params.width
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    struct Width {
+        width: usize,
+    }
+
+    impl Default for Width {
+        fn default() -> Self {
+            Width { width: 101 }
+        }
+    }
+
+    impl Params for Width {
+        const DAY: u8 = 14;
+
+        fn set(&mut self, name: &str, value: &str) -> Result<()> {
+            match name {
+                "width" => self.width = crate::params::parse_param(name, value)?,
+                _ => crate::params::unknown_param(name)?,
+            }
+            Ok(())
+        }
+    }
+
+    fn measure() -> Measure {
+        let ms = Duration::from_millis;
+        Measure {
+            day: 1,
+            parser: "day01::parse_input",
+            solver: "day01::part1",
+            runs: 3,
+            parse: Timings::new(vec![ms(2), ms(1), ms(3)]),
+            solve: Timings::new(vec![ms(5), ms(4), ms(6)]),
+            answer: "42".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_timings() {
+        let ms = Duration::from_millis;
+        let timings = Timings::new(vec![ms(3), ms(1), ms(7), ms(2), ms(5)]);
+        assert_eq!(
+            (timings.min, timings.median, timings.max),
+            (ms(1), ms(3), ms(7))
+        );
+        assert_eq!(timings.to_string(), "min 1ms, median 3ms, max 7ms");
+    }
+
+    #[test]
+    fn test_bench() {
+        let solution: &dyn Bench = solution! {1, failable_parser!{ parse }, solver!{ double }};
+        let measure = solution.bench("21\n", 5, &[]).unwrap();
+        assert_eq!(measure.day, 1);
+        assert_eq!(measure.parser, "parse");
+        assert_eq!(measure.solver, "double");
+        assert_eq!(measure.runs, 5);
+        assert!(measure.parse.min <= measure.parse.median);
+        assert!(measure.solve.median <= measure.solve.max);
+        assert_eq!(measure.answer, "42");
+
+        assert!(solution.bench("x", 5, &[]).is_err());
+        assert!(solution.bench("21", 0, &[]).is_err());
+    }
+
+    #[test]
+    fn test_bench_tuned() {
+        let solution: &dyn Bench = crate::tuned! {14, failable_parser!{ parse }, solver!{ scale }};
+        assert_eq!(solution.bench("21", 1, &[]).unwrap().answer, "2121");
+        let overrides = ["day14.width=2".parse().unwrap()];
+        assert_eq!(solution.bench("21", 1, &overrides).unwrap().answer, "42");
+        let overrides = ["day14.height=2".parse().unwrap()];
+        assert!(solution.bench("21", 1, &overrides).is_err());
+
+        let solution: &dyn Bench =
+            crate::tuned! {14, crate::params_parser!{ parse_narrow }, solver!{ scale }};
+        let measure = solution.bench("21", 1, &[]).unwrap();
+        assert_eq!(
+            (measure.parser, measure.answer.as_str()),
+            ("parse_narrow", "2121")
+        );
+        let overrides = ["day14.width=20".parse().unwrap()];
+        assert!(solution.bench("21", 1, &overrides).is_err());
+    }
+
+    #[test]
+    fn test_to_csv() {
+        assert_eq!(
+            to_csv(&[measure()]),
+            "day,parser,solver,runs,parse_min_ns,parse_median_ns,parse_max_ns,solve_min_ns,solve_median_ns,solve_max_ns\n\
+             1,day01::parse_input,day01::part1,3,1000000,2000000,3000000,4000000,5000000,6000000\n"
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        assert_eq!(
+            to_json(&[measure(), measure()]).lines().collect::<Vec<_>>(),
+            vec![
+                "[",
+                "  {\"day\": 1, \"parser\": \"day01::parse_input\", \"solver\": \"day01::part1\", \"runs\": 3, \"parse_min_ns\": 1000000, \"parse_median_ns\": 2000000, \"parse_max_ns\": 3000000, \"solve_min_ns\": 4000000, \"solve_median_ns\": 5000000, \"solve_max_ns\": 6000000},",
+                "  {\"day\": 1, \"parser\": \"day01::parse_input\", \"solver\": \"day01::part1\", \"runs\": 3, \"parse_min_ns\": 1000000, \"parse_median_ns\": 2000000, \"parse_max_ns\": 3000000, \"solve_min_ns\": 4000000, \"solve_median_ns\": 5000000, \"solve_max_ns\": 6000000}",
+                "]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escaping() {
+        // the names come from `stringify!`, so a closure can put anything in them
+        let measure = Measure {
+            parser: "|s| parse(s, \"a,b\")",
+            solver: "|x| x\\\n",
+            ..measure()
+        };
+        assert_eq!(
+            to_csv(std::slice::from_ref(&measure))
+                .lines()
+                .collect::<Vec<_>>()[1..],
+            [
+                "1,\"|s| parse(s, \"\"a,b\"\")\",\"|x| x\\",
+                "\",3,1000000,2000000,3000000,4000000,5000000,6000000",
+            ]
+        );
+        assert!(to_json(&[measure])
+            .contains("\"parser\": \"|s| parse(s, \\\"a,b\\\")\", \"solver\": \"|x| x\\\\\\n\", "));
+        assert_eq!(json_string("\u{1}\t"), "\"\\u0001\\t\"");
+    }
+}
@@ -1,12 +1,14 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use counter::Counter;
 
+use crate::parsers::{parse_at, split_once_at};
+
 pub fn parse_input(input: &str) -> Result<Vec<(i32, i32)>> {
     input
         .lines()
         .map(|line| {
-            let (p1, p2) = line.split_once("   ").context("bad input")?;
-            Ok((p1.parse()?, p2.parse()?))
+            let (p1, p2) = split_once_at(input, line, "   ")?;
+            Ok((parse_at(input, p1)?, parse_at(input, p2)?))
         })
         .collect()
 }
@@ -29,15 +31,7 @@ pub fn part2(input: Vec<(i32, i32)>) -> i32 {
 #[cfg(test)]
 mod test {
     use super::*;
-    use indoc::indoc;
-    const INPUT: &str = indoc! {
-    "3   4
-    4   3
-    2   5
-    1   3
-    3   9
-    3   3
-    "};
+    const INPUT: &str = include_str!("../fixtures/day01.txt");
 
     #[test]
     fn test_parse_input() {
@@ -46,6 +40,20 @@ mod test {
         assert_eq!(vec, vec![(3, 4), (4, 3), (2, 5), (1, 3), (3, 9), (3, 3)]);
     }
 
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("3   4\n4   x\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 5: invalid value \"x\": invalid digit found in string"
+        );
+        let e = parse_input("3   4\n4 3\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 1: expected \"   \" in \"4 3\""
+        );
+    }
+
     #[test]
     fn test_part1() {
         let vec = parse_input(INPUT).unwrap();
@@ -1,17 +1,23 @@
 use std::str::FromStr;
 
+use anyhow::Result;
 use itertools::Itertools;
 
+use crate::parsers::{parse_at, ParseError};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Record {
     levels: Vec<i32>,
 }
 
 impl FromStr for Record {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let levels: Vec<i32> = s.split_whitespace().map(|x| x.parse().unwrap()).collect();
+        let levels = s
+            .split_whitespace()
+            .map(|x| parse_at(s, x))
+            .collect::<Result<_, _>>()?;
         Ok(Record { levels })
     }
 }
@@ -45,8 +51,15 @@ impl Record {
     }
 }
 
-pub fn parse_input(input: &str) -> Vec<Record> {
-    input.lines().map(|line| line.parse().unwrap()).collect()
+pub fn parse_input(input: &str) -> Result<Vec<Record>> {
+    input
+        .lines()
+        .map(|line| {
+            Ok(line
+                .parse()
+                .map_err(|e: ParseError| e.rebase(input, line))?)
+        })
+        .collect()
 }
 
 pub fn part1(input: Vec<Record>) -> usize {
@@ -60,20 +73,12 @@ pub fn part2(input: Vec<Record>) -> usize {
 #[cfg(test)]
 mod test {
     use super::*;
-    use indoc::indoc;
 
-    const INPUT: &str = indoc! {
-    "7 6 4 2 1
-        1 2 7 8 9
-        9 7 6 2 1
-        1 3 2 4 5
-        8 6 4 4 1
-        1 3 6 7 9
-        "};
+    const INPUT: &str = include_str!("../fixtures/day02.txt");
 
     #[test]
     fn test_parse_input() {
-        let vec = parse_input(INPUT);
+        let vec = parse_input(INPUT).unwrap();
         assert_eq!(vec.len(), 6);
         assert_eq!(
             vec[0],
@@ -99,15 +104,24 @@ mod test {
         assert!(!r.is_safe());
     }
 
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("1 2 3\n4 5 -\n").unwrap_err();
+        assert_eq!(
+            e.downcast_ref::<ParseError>().map(|e| (e.line, e.column)),
+            Some((2, 5))
+        );
+    }
+
     #[test]
     fn test_part1() {
-        let vec = parse_input(INPUT);
+        let vec = parse_input(INPUT).unwrap();
         assert_eq!(part1(vec), 2);
     }
 
     #[test]
     fn test_part2() {
-        let vec = parse_input(INPUT);
+        let vec = parse_input(INPUT).unwrap();
         assert_eq!(part2(vec), 4);
     }
 }
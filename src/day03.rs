@@ -1,65 +1,98 @@
+use anyhow::Result;
 use regex::Regex;
 use std::sync::LazyLock;
 
+use crate::parsers::{parse_at, ParseError};
+
 static RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"mul\((\d+),(\d+)\)").unwrap());
 
-pub fn parse_input(input: &str) -> Vec<(u32, u32)> {
-    RE.captures_iter(input)
+/// Parse the instructions of `s`, a slice of `input`
+fn parse_instructions(input: &str, s: &str) -> Result<Vec<(u32, u32)>, ParseError> {
+    RE.captures_iter(s)
         .map(|c| {
             let (_, [a, b]) = c.extract();
-            (a.parse().unwrap(), b.parse().unwrap())
+            Ok((parse_at(input, a)?, parse_at(input, b)?))
         })
         .collect()
 }
 
+pub fn parse_input(input: &str) -> Result<Vec<(u32, u32)>> {
+    Ok(parse_instructions(input, input)?)
+}
+
 pub fn part1(input: Vec<(u32, u32)>) -> u32 {
     input.iter().fold(0, |acc, (a, b)| acc + a * b)
 }
 
-pub fn parse_input_p2(input: &str) -> Vec<(u32, u32)> {
-    let l = input.replace("\n", "");
-    l.split("don't()")
+/// Move an error found in `joined`, which is `input` without its newlines, back to `input`
+fn locate_in(input: &str, e: ParseError) -> ParseError {
+    let offset = input
+        .char_indices()
+        .filter(|(_, c)| *c != '\n')
+        .nth(e.column - 1)
+        .map_or(input.len(), |(i, _)| i);
+    ParseError::at(input, &input[offset..], e.message)
+}
+
+pub fn parse_input_p2(input: &str) -> Result<Vec<(u32, u32)>> {
+    // the newlines are dropped, so that an instruction can span several lines
+    let joined = input.replace('\n', "");
+    let enabled = joined
+        .split("don't()")
         .enumerate()
         .filter_map(|(i, s)| {
             if i == 0 {
-                Some(parse_input(s))
+                Some(s)
             } else if let Some((_excluded, not_excluded)) = s.split_once("do()") {
-                Some(parse_input(not_excluded))
+                Some(not_excluded)
             } else {
                 None
             }
         })
-        .flatten()
-        .collect::<Vec<_>>()
+        .map(|s| parse_instructions(&joined, s).map_err(|e| locate_in(input, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(enabled.into_iter().flatten().collect())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use indoc::indoc;
     use pretty_assertions::assert_eq;
-    const INPUT: &str = indoc! {
-        "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))"
-    };
-    const INPUT_P2: &str = indoc! {
-        "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))"
-    };
+    const INPUT: &str = include_str!("../fixtures/day03.txt");
+    const INPUT_P2: &str = include_str!("../fixtures/day03_p2.txt");
 
     #[test]
     fn test_parse_input() {
         let expected = vec![(2, 4), (5, 5), (11, 8), (8, 5)];
-        assert_eq!(parse_input(INPUT), expected);
+        assert_eq!(parse_input(INPUT).unwrap(), expected);
     }
 
     #[test]
     fn test_part1() {
         let expected = 161;
-        assert_eq!(part1(parse_input(INPUT)), expected);
+        assert_eq!(part1(parse_input(INPUT).unwrap()), expected);
     }
 
     #[test]
     fn test_parse_input_p2() {
         let expected = vec![(2, 4), (8, 5)];
-        assert_eq!(parse_input_p2(INPUT_P2), expected);
+        assert_eq!(parse_input_p2(INPUT_P2).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_input_p2_across_lines() {
+        assert_eq!(
+            parse_input_p2("mul(1,\n2)mul(3,4)").unwrap(),
+            vec![(1, 2), (3, 4)]
+        );
+        assert_eq!(parse_input_p2("don'\nt()mul(3,4)").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("mul(1,2)\nxmul(99999999999,2)").unwrap_err();
+        assert!(e.to_string().starts_with("line 2, column 6: invalid value"));
+        let e = parse_input_p2("mul(1,2)\nxmul(99999999999,2)").unwrap_err();
+        assert!(e.to_string().starts_with("line 2, column 6: invalid value"));
     }
 }
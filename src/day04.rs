@@ -1,36 +1,29 @@
+use anyhow::Result;
 use num::Complex;
-use rustc_hash::FxHashMap as HashMap;
-
-type Grid = HashMap<Complex<isize>, char>;
-
-const DIRECTIONS: [Complex<isize>; 8] = [
-    Complex::new(0, 1),
-    Complex::new(0, -1),
-    Complex::new(1, 0),
-    Complex::new(1, 1),
-    Complex::new(1, -1),
-    Complex::new(-1, 0),
-    Complex::new(-1, 1),
-    Complex::new(-1, -1),
-];
-
-pub fn parse_input(input: &str) -> (Vec<Complex<isize>>, Grid) {
-    let mut xs = Vec::new();
-    let mut grid = HashMap::default();
-    for (y, line) in input.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            let pos = Complex::new(x as isize, y as isize);
-            if c == 'X' {
-                xs.push(pos);
+
+use crate::grid::{Grid, Pos, DIRECTIONS_8};
+
+/// Parse the letters of the grid, keeping the positions of `letter`
+fn parse_letters(input: &str, letter: char) -> Result<(Vec<Pos>, Grid<char>)> {
+    let mut positions = Vec::new();
+    let grid = Grid::try_parse(input, |pos, c| match c {
+        'X' | 'M' | 'A' | 'S' => {
+            if c == letter {
+                positions.push(pos);
             }
-            grid.insert(pos, c);
+            Ok(c)
         }
-    }
+        c => Err(format!("unexpected character {c:?}")),
+    })?;
 
-    (xs, grid)
+    Ok((positions, grid))
 }
 
-fn check_xmas(x_pos: Complex<isize>, direction: Complex<isize>, grid: &Grid) -> bool {
+pub fn parse_input(input: &str) -> Result<(Vec<Pos>, Grid<char>)> {
+    parse_letters(input, 'X')
+}
+
+fn check_xmas(x_pos: Pos, direction: Pos, grid: &Grid<char>) -> bool {
     for (k, c) in "MAS".chars().enumerate() {
         let new_pos = x_pos + (k as isize + 1) * direction;
         if let Some(val) = grid.get(&new_pos) {
@@ -44,36 +37,24 @@ fn check_xmas(x_pos: Complex<isize>, direction: Complex<isize>, grid: &Grid) ->
     true
 }
 
-fn check_all_directions(x_pos: Complex<isize>, grid: &Grid) -> usize {
-    DIRECTIONS
+fn check_all_directions(x_pos: Pos, grid: &Grid<char>) -> usize {
+    DIRECTIONS_8
         .into_iter()
         .filter(|&direction| check_xmas(x_pos, direction, grid))
         .count()
 }
 
-pub fn part1((xs, grid): (Vec<Complex<isize>>, Grid)) -> usize {
+pub fn part1((xs, grid): (Vec<Pos>, Grid<char>)) -> usize {
     xs.into_iter()
         .map(|x_pos| check_all_directions(x_pos, &grid))
         .sum()
 }
 
-pub fn parse_input_p2(input: &str) -> (Vec<Complex<isize>>, Grid) {
-    let mut a_s = Vec::new();
-    let mut grid = HashMap::default();
-    for (y, line) in input.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            let pos = Complex::new(x as isize, y as isize);
-            if c == 'A' {
-                a_s.push(pos);
-            }
-            grid.insert(pos, c);
-        }
-    }
-
-    (a_s, grid)
+pub fn parse_input_p2(input: &str) -> Result<(Vec<Pos>, Grid<char>)> {
+    parse_letters(input, 'A')
 }
 
-fn check_cross_mas(a_pos: Complex<isize>, grid: &Grid) -> bool {
+fn check_cross_mas(a_pos: Pos, grid: &Grid<char>) -> bool {
     let top_left = a_pos + Complex::new(-1, -1);
     let bottom_right = a_pos + Complex::new(1, 1);
     let top_right = a_pos + Complex::new(1, -1);
@@ -105,7 +86,7 @@ fn check_cross_mas(a_pos: Complex<isize>, grid: &Grid) -> bool {
     true
 }
 
-pub fn part2((a_s, grid): (Vec<Complex<isize>>, Grid)) -> usize {
+pub fn part2((a_s, grid): (Vec<Pos>, Grid<char>)) -> usize {
     a_s.into_iter()
         .filter(|&a_pos| check_cross_mas(a_pos, &grid))
         .count()
@@ -114,35 +95,28 @@ pub fn part2((a_s, grid): (Vec<Complex<isize>>, Grid)) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use indoc::indoc;
     use pretty_assertions::assert_eq;
 
-    const INPUT: &str = indoc! {
-        "MMMSXXMASM
-        MSAMXMSMSA
-        AMXSXMAAMM
-        MSAMASMSMX
-        XMASAMXAMM
-        XXAMMXXAMA
-        SMSMSASXSS
-        SAXAMASAAA
-        MAMMMXMMMM
-        MXMXAXMASX
-        "
-    };
+    const INPUT: &str = include_str!("../fixtures/day04.txt");
 
     #[test]
     fn test_parse_input() {
-        let (xs, grid) = parse_input(INPUT);
+        let (xs, grid) = parse_input(INPUT).unwrap();
         assert_eq!(xs.len(), INPUT.chars().filter(|c| *c == 'X').count());
         assert_eq!(xs[0], Complex::new(4, 0));
         assert_eq!(grid[&Complex::new(0, 0)], 'M');
         assert_eq!(grid[&Complex::new(9, 9)], 'X');
     }
 
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("XMAS\nXMAZ").unwrap_err();
+        assert_eq!(e.to_string(), "line 2, column 4: unexpected character 'Z'");
+    }
+
     #[test]
     fn test_check_xmas() {
-        let (_xs, grid) = parse_input(INPUT);
+        let (_xs, grid) = parse_input(INPUT).unwrap();
         let x_pos = Complex::new(5, 0);
         assert!(check_xmas(x_pos, Complex::new(1, 0), &grid));
         assert!(!check_xmas(x_pos, Complex::new(1, 1), &grid));
@@ -152,26 +126,26 @@ mod tests {
 
     #[test]
     fn test_check_all_directions() {
-        let (_xs, grid) = parse_input(INPUT);
+        let (_xs, grid) = parse_input(INPUT).unwrap();
         let x_pos = Complex::new(3, 9);
         assert_eq!(check_all_directions(x_pos, &grid), 2);
     }
 
     #[test]
     fn test_part1() {
-        let (xs, grid) = parse_input(INPUT);
+        let (xs, grid) = parse_input(INPUT).unwrap();
         assert_eq!(part1((xs, grid)), 18);
     }
 
     #[test]
     fn test_check_cross_mass() {
-        let (_a_s, grid) = parse_input_p2(INPUT);
+        let (_a_s, grid) = parse_input_p2(INPUT).unwrap();
         assert!(check_cross_mas(Complex::new(2, 1), &grid));
     }
 
     #[test]
     fn test_part2() {
-        let (a_s, grid) = parse_input_p2(INPUT);
+        let (a_s, grid) = parse_input_p2(INPUT).unwrap();
         assert_eq!(part2((a_s, grid)), 9);
     }
 }
@@ -1,28 +1,36 @@
+use anyhow::Result;
 use rustc_hash::FxHashMap as HashMap;
 use rustc_hash::FxHashSet as HashSet;
 
+use crate::parsers::{parse_at, split_once_at, ParseError};
+
 type Rules = HashMap<usize, HashSet<usize>>;
 type Pages = Vec<Vec<usize>>;
 
-pub fn parse_input(input: &str) -> (Rules, Pages) {
-    let (rules_list, prints) = input.split_once("\n\n").unwrap();
+pub fn parse_input(input: &str) -> Result<(Rules, Pages)> {
+    let (rules_list, prints) = input.split_once("\n\n").ok_or_else(|| {
+        ParseError::at_end(
+            input,
+            "expected an empty line between the rules and the updates",
+        )
+    })?;
     let pages = prints
         .lines()
-        .map(|line| line.split(",").map(|p| p.parse().unwrap()).collect())
-        .collect();
+        .map(|line| line.split(",").map(|p| parse_at(input, p)).collect())
+        .collect::<Result<_, _>>()?;
 
     let mut rules = HashMap::default();
 
     for line in rules_list.lines() {
-        let (before, after) = line.split_once("|").unwrap();
-        let before = before.parse().unwrap();
-        let after = after.parse().unwrap();
+        let (before, after) = split_once_at(input, line, "|")?;
+        let before = parse_at(input, before)?;
+        let after = parse_at(input, after)?;
         rules
             .entry(after)
             .or_insert_with(HashSet::default)
             .insert(before);
     }
-    (rules, pages)
+    Ok((rules, pages))
 }
 
 fn check_page(page: &[usize], rules: &Rules) -> bool {
@@ -72,51 +80,39 @@ pub fn part2((rules, pages): (Rules, Pages)) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use indoc::indoc;
     use pretty_assertions::assert_eq;
 
-    const INPUT: &str = indoc! {
-        "47|53
-        97|13
-        97|61
-        97|47
-        75|29
-        61|13
-        75|53
-        29|13
-        97|29
-        53|29
-        61|53
-        97|53
-        61|29
-        47|13
-        75|47
-        97|75
-        47|61
-        75|61
-        47|29
-        75|13
-        53|13
-
-        75,47,61,53,29
-        97,61,53,29,13
-        75,29,13
-        75,97,47,61,53
-        61,13,29
-        97,13,75,29,47"
-    };
+    const INPUT: &str = include_str!("../fixtures/day05.txt");
 
     #[test]
     fn test_parse_input() {
-        let (rules, pages) = parse_input(INPUT);
+        let (rules, pages) = parse_input(INPUT).unwrap();
         assert_eq!(rules[&75], HashSet::from_iter([97]));
         assert_eq!(pages.len(), 6);
         assert_eq!(pages[0], vec![75, 47, 61, 53, 29]);
     }
 
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("47|53\n97-13\n\n75,47").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 1: expected \"|\" in \"97-13\""
+        );
+        let e = parse_input("47|53\n97|13\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 1: expected an empty line between the rules and the updates"
+        );
+        let e = parse_input("47|53\n\n75,4a").unwrap_err();
+        assert!(e
+            .to_string()
+            .starts_with("line 3, column 4: invalid value \"4a\""));
+    }
+
     #[test]
     fn test_check_page() {
-        let (rules, pages) = parse_input(INPUT);
+        let (rules, pages) = parse_input(INPUT).unwrap();
         assert!(check_page(&pages[0], &rules));
         assert!(check_page(&pages[1], &rules));
         assert!(check_page(&pages[2], &rules));
@@ -127,13 +123,13 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let (rules, pages) = parse_input(INPUT);
+        let (rules, pages) = parse_input(INPUT).unwrap();
         assert_eq!(part1((rules, pages)), 143);
     }
 
     #[test]
     fn test_part2() {
-        let (rules, pages) = parse_input(INPUT);
+        let (rules, pages) = parse_input(INPUT).unwrap();
         assert_eq!(part2((rules, pages)), 123);
     }
 }
@@ -1,10 +1,12 @@
+use anyhow::Result;
 use num::Complex;
-use rustc_hash::FxHashMap as HashMap;
 use rustc_hash::FxHashSet as HashSet;
 
-type Pos = Complex<isize>;
+use crate::grid::{self, Pos};
+use crate::parsers::ParseError;
+
 type Dir = Complex<isize>;
-type Grid = HashMap<Pos, State>;
+type Grid = grid::DenseGrid<State>;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum State {
@@ -12,24 +14,28 @@ pub enum State {
     Obstacle,
 }
 
-pub fn parse_input(input: &str) -> (Pos, Grid) {
-    let mut start = Complex::default();
-    let mut grid = HashMap::default();
-    for (j, line) in input.lines().enumerate() {
-        for (i, c) in line.chars().enumerate() {
-            let (pos, state) = match c {
-                '^' => {
-                    start = Complex::new(i as isize, j as isize);
-                    (Complex::new(i as isize, j as isize), State::Empty)
-                }
-                '.' => (Complex::new(i as isize, j as isize), State::Empty),
-                '#' => (Complex::new(i as isize, j as isize), State::Obstacle),
-                _ => panic!("unexpected character"),
-            };
-            grid.insert(pos, state);
+impl std::fmt::Display for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            State::Empty => write!(f, "."),
+            State::Obstacle => write!(f, "#"),
         }
     }
-    (start, grid)
+}
+
+pub fn parse_input(input: &str) -> Result<(Pos, Grid)> {
+    let mut start = None;
+    let grid = Grid::try_parse(input, |pos, c| match c {
+        '^' => {
+            start = Some(pos);
+            Ok(State::Empty)
+        }
+        '.' => Ok(State::Empty),
+        '#' => Ok(State::Obstacle),
+        c => Err(format!("unexpected character {c:?}")),
+    })?;
+    let start = start.ok_or_else(|| ParseError::at_end(input, "no guard '^' in the map"))?;
+    Ok((start, grid))
 }
 
 pub fn part1((start, grid): (Pos, Grid)) -> usize {
@@ -51,26 +57,11 @@ pub fn part1((start, grid): (Pos, Grid)) -> usize {
 }
 
 fn _print_grid_and_steps(grid: &Grid, steps: &HashSet<(Pos, Dir)>) {
-    let size = grid.iter().map(|(a, _)| a.re).max().unwrap();
-    for j in 0..=size {
-        let mut s = String::new();
-        for i in 0..=size {
-            let pos = Complex::new(i, j);
-            let c = match steps.iter().find(|(p, _d)| *p == pos) {
-                Some(_) => 'X',
-                None => match grid.get(&pos) {
-                    Some(state) => match state {
-                        State::Empty => '.',
-                        State::Obstacle => '#',
-                    },
-                    None => ' ',
-                },
-            };
-            s = format!("{}{}", s, c);
-        }
-        println!("{}", s);
+    let mut grid: grid::DenseGrid<String> = grid.iter().map(|(p, s)| (p, s.to_string())).collect();
+    for (pos, _) in steps {
+        grid[pos] = String::from("X");
     }
-    println!();
+    println!("{grid}");
 }
 
 fn find_loop(position: Pos, direction: Dir, steps: &HashSet<(Pos, Dir)>, grid: &Grid) -> bool {
@@ -78,7 +69,7 @@ fn find_loop(position: Pos, direction: Dir, steps: &HashSet<(Pos, Dir)>, grid: &
     // we add a block at position + direction, so the new direction to check is rotated
     // by 90°
     let mut grid = grid.clone();
-    grid.insert(position + direction, State::Obstacle);
+    grid[position + direction] = State::Obstacle;
     let mut direction = direction * Complex::i();
     let mut position = position;
     while let Some(state) = grid.get(&(position + direction)) {
@@ -104,7 +95,7 @@ pub fn part2((start, grid): (Pos, Grid)) -> usize {
     let mut direction = -Complex::i();
     let mut pos = start;
     let mut steps = HashSet::default();
-    let mut blocks: HashSet<Complex<isize>> = HashSet::default();
+    let mut blocks: HashSet<Pos> = HashSet::default();
     steps.insert((pos, direction));
     while let Some(state) = grid.get(&(pos + direction)) {
         match state {
@@ -130,41 +121,36 @@ pub fn part2((start, grid): (Pos, Grid)) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use indoc::indoc;
     use pretty_assertions::assert_eq;
 
-    const INPUT: &str = indoc! {
-        "....#.....
-        .........#
-        ..........
-        ..#.......
-        .......#..
-        ..........
-        .#..^.....
-        ........#.
-        #.........
-        ......#...
-        "
-    };
+    const INPUT: &str = include_str!("../fixtures/day06.txt");
 
     #[test]
     fn test_parse_input() {
-        let (start, grid) = parse_input(INPUT);
+        let (start, grid) = parse_input(INPUT).unwrap();
         assert_eq!(start, Complex::new(4, 6));
         assert_eq!(grid[&Complex::new(4, 6)], State::Empty);
         assert_eq!(grid[&Complex::new(4, 0)], State::Obstacle);
         assert_eq!(grid[&Complex::new(0, 0)], State::Empty);
     }
 
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("..#\n.^X\n").unwrap_err();
+        assert_eq!(e.to_string(), "line 2, column 3: unexpected character 'X'");
+        let e = parse_input("..#\n...\n").unwrap_err();
+        assert_eq!(e.to_string(), "line 3, column 1: no guard '^' in the map");
+    }
+
     #[test]
     fn test_part1() {
-        let (start, grid) = parse_input(INPUT);
+        let (start, grid) = parse_input(INPUT).unwrap();
         assert_eq!(part1((start, grid)), 41);
     }
 
     #[test]
     fn test_part2() {
-        let (start, grid) = parse_input(INPUT);
+        let (start, grid) = parse_input(INPUT).unwrap();
         assert_eq!(part2((start, grid)), 6);
     }
 }
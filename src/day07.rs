@@ -1,11 +1,18 @@
-pub fn parse_input(input: &str) -> Vec<(usize, Vec<usize>)> {
+use anyhow::Result;
+
+use crate::parsers::{parse_at, split_once_at};
+
+pub fn parse_input(input: &str) -> Result<Vec<(usize, Vec<usize>)>> {
     input
         .lines()
         .map(|line| {
-            let (val, rest) = line.split_once(": ").unwrap();
-            let val = val.parse().unwrap();
-            let rest = rest.split(" ").map(|s| s.parse().unwrap()).collect();
-            (val, rest)
+            let (val, rest) = split_once_at(input, line, ": ")?;
+            let val = parse_at(input, val)?;
+            let rest = rest
+                .split(" ")
+                .map(|s| parse_at(input, s))
+                .collect::<Result<_, _>>()?;
+            Ok((val, rest))
         })
         .collect()
 }
@@ -60,39 +67,41 @@ pub fn part2(input: Vec<(usize, Vec<usize>)>) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use indoc::indoc;
     use pretty_assertions::assert_eq;
 
-    const INPUT: &str = indoc! {
-        "190: 10 19
-        3267: 81 40 27
-        83: 17 5
-        156: 15 6
-        7290: 6 8 6 15
-        161011: 16 10 13
-        192: 17 8 14
-        21037: 9 7 18 13
-        292: 11 6 16 20
-        "
-    };
+    const INPUT: &str = include_str!("../fixtures/day07.txt");
 
     #[test]
     fn test_parse_input() {
-        let input = parse_input(INPUT);
+        let input = parse_input(INPUT).unwrap();
         assert_eq!(input.len(), 9);
         assert_eq!(input[0].0, 190);
         assert_eq!(input[0].1, vec![10, 19]);
     }
 
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("190: 10 19\n3267 81 40 27").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 1: expected \": \" in \"3267 81 40 27\""
+        );
+        let e = parse_input("190: 10 19\n3267: 81  40").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 10: invalid value \"\": cannot parse integer from empty string"
+        );
+    }
+
     #[test]
     fn test_part1() {
-        let input = parse_input(INPUT);
+        let input = parse_input(INPUT).unwrap();
         assert_eq!(part1(input), 3749);
     }
 
     #[test]
     fn test_part2() {
-        let input = parse_input(INPUT);
+        let input = parse_input(INPUT).unwrap();
         assert_eq!(part2(input), 11387);
     }
 }
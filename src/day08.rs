@@ -1,33 +1,30 @@
+use anyhow::Result;
 use itertools::Itertools;
-use num::Complex;
 use rustc_hash::FxHashMap as HashMap;
 use rustc_hash::FxHashSet as HashSet;
 
-type Pos = Complex<isize>;
-type Grid = HashMap<char, Vec<Pos>>;
+use crate::grid::{Grid, Pos};
 
-pub fn parse_input(input: &str) -> (Grid, isize, isize) {
-    let mut grid: Grid = HashMap::default();
-    let width = input.lines().next().unwrap().len() as isize;
-    let height = input.lines().count() as isize;
-    for (j, line) in input.lines().enumerate() {
-        for (i, c) in line.chars().enumerate() {
-            match c {
-                '.' => (),
-                c => {
-                    grid.entry(c)
-                        .or_default()
-                        .push(Complex::new(i as isize, j as isize));
-                }
-            }
+type Antennas = HashMap<char, Vec<Pos>>;
+
+pub fn parse_input(input: &str) -> Result<(Antennas, Grid<char>)> {
+    let grid = Grid::try_parse(input, |_, c| {
+        if c == '.' || c.is_ascii_alphanumeric() {
+            Ok(c)
+        } else {
+            Err(format!("unexpected character {c:?}"))
         }
+    })?;
+    let mut antennas: Antennas = HashMap::default();
+    for (pos, c) in grid.iter().filter(|(_, c)| **c != '.') {
+        antennas.entry(*c).or_default().push(pos);
     }
-    (grid, width, height)
+    Ok((antennas, grid))
 }
 
-pub fn part1((grid, width, height): (Grid, isize, isize)) -> usize {
+pub fn part1((antennas, grid): (Antennas, Grid<char>)) -> usize {
     let mut antinodes = HashSet::default();
-    for positions in grid.values() {
+    for positions in antennas.values() {
         for v in positions.iter().combinations(2) {
             let p1 = v[0];
             let p2 = v[1];
@@ -36,36 +33,25 @@ pub fn part1((grid, width, height): (Grid, isize, isize)) -> usize {
             antinodes.insert(p1 + diff);
         }
     }
-    antinodes
-        .iter()
-        .filter(|p| p.re >= 0 && p.re < width && p.im >= 0 && p.im < height)
-        .count()
+    antinodes.iter().filter(|p| grid.contains(p)).count()
 }
 
-pub fn part2((grid, width, height): (Grid, isize, isize)) -> usize {
+pub fn part2((antennas, grid): (Antennas, Grid<char>)) -> usize {
     let mut antinodes = HashSet::default();
-    for positions in grid.values() {
+    for positions in antennas.values() {
         for v in positions.iter().combinations(2) {
             let p1 = v[0];
             let p2 = v[1];
             let diff = p1 - p2;
             // positive
             let mut k = 0;
-            while (p1 + diff * k).re < width
-                && (p1 + diff * k).im < height
-                && (p1 + diff * k).re >= 0
-                && (p1 + diff * k).im >= 0
-            {
+            while grid.contains(&(p1 + diff * k)) {
                 antinodes.insert(p1 + diff * k);
                 k += 1;
             }
             // negative
             k = 0;
-            while (p2 + diff * k).re < width
-                && (p2 + diff * k).im < height
-                && (p2 + diff * k).re >= 0
-                && (p2 + diff * k).im >= 0
-            {
+            while grid.contains(&(p2 + diff * k)) {
                 antinodes.insert(p2 + diff * k);
                 k -= 1;
             }
@@ -77,33 +63,19 @@ pub fn part2((grid, width, height): (Grid, isize, isize)) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use indoc::indoc;
+    use num::Complex;
     use pretty_assertions::assert_eq;
 
-    const INPUT: &str = indoc! {
-        "............
-        ........0...
-        .....0......
-        .......0....
-        ....0.......
-        ......A.....
-        ............
-        ............
-        ........A...
-        .........A..
-        ............
-        ............
-        "
-    };
+    const INPUT: &str = include_str!("../fixtures/day08.txt");
 
     #[test]
     fn test_parse_input() {
-        let (grid, width, height) = parse_input(INPUT);
-        assert_eq!(width, 12);
-        assert_eq!(height, 12);
-        assert_eq!(grid.len(), 2);
+        let (antennas, grid) = parse_input(INPUT).unwrap();
+        assert_eq!(grid.width(), 12);
+        assert_eq!(grid.height(), 12);
+        assert_eq!(antennas.len(), 2);
         assert_eq!(
-            grid[&'0'],
+            antennas[&'0'],
             vec![
                 Complex::new(8, 1),
                 Complex::new(5, 2),
@@ -113,15 +85,21 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("..a\n.#.").unwrap_err();
+        assert_eq!(e.to_string(), "line 2, column 2: unexpected character '#'");
+    }
+
     #[test]
     fn test_part1() {
-        let input = parse_input(INPUT);
+        let input = parse_input(INPUT).unwrap();
         assert_eq!(part1(input), 14);
     }
 
     #[test]
     fn test_part2() {
-        let input = parse_input(INPUT);
+        let input = parse_input(INPUT).unwrap();
         assert_eq!(part2(input), 34);
     }
 }
@@ -1,14 +1,24 @@
 use std::ops::Range;
 
+use anyhow::Result;
+
+use crate::parsers::ParseError;
+
 type Values = Vec<(usize, Range<usize>)>;
 type Blanks = Vec<Range<usize>>;
 
-pub fn parse_input(input: &str) -> (Values, Blanks) {
+pub fn parse_input(input: &str) -> Result<(Values, Blanks)> {
     let mut positions = vec![];
     let mut blanks = vec![];
     let mut current_pos = 0;
-    for (i, c) in input.trim_end().chars().enumerate() {
-        let val = c.to_digit(10).unwrap() as usize;
+    for (i, (offset, c)) in input.trim_end().char_indices().enumerate() {
+        let val = c.to_digit(10).ok_or_else(|| {
+            ParseError::at(
+                input,
+                &input[offset..],
+                format!("expected a digit, found {c:?}"),
+            )
+        })? as usize;
         if i % 2 == 0 {
             positions.push((i / 2, current_pos..(current_pos + val)));
         } else {
@@ -16,7 +26,7 @@ pub fn parse_input(input: &str) -> (Values, Blanks) {
         }
         current_pos += val;
     }
-    (positions, blanks)
+    Ok((positions, blanks))
 }
 
 pub fn part1((values, blanks): (Values, Blanks)) -> usize {
@@ -102,11 +112,11 @@ mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
 
-    const INPUT: &str = "2333133121414131402";
+    const INPUT: &str = include_str!("../fixtures/day09.txt");
 
     #[test]
     fn test_parse_input() {
-        let (values, blanks) = parse_input(INPUT);
+        let (values, blanks) = parse_input(INPUT).unwrap();
         assert_eq!(values.len(), 10);
         assert_eq!(blanks.len(), 9);
         assert_eq!(values[0], (0, 0..2));
@@ -115,18 +125,27 @@ mod tests {
         assert_eq!(blanks[8], 40..40);
     }
 
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("2333-1").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 1, column 5: expected a digit, found '-'"
+        );
+    }
+
     #[test]
     fn test_part1() {
-        let input = parse_input(INPUT);
+        let input = parse_input(INPUT).unwrap();
         assert_eq!(part1(input), 1928);
     }
 
     #[test]
     fn test_part2() {
-        let input = parse_input(INPUT);
+        let input = parse_input(INPUT).unwrap();
         assert_eq!(part2(input), 2858);
         // panic!("show the logs");
-        assert_eq!(part2(parse_input("12345")), 132);
+        assert_eq!(part2(parse_input("12345").unwrap()), 132);
     }
 }
 
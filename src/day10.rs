@@ -1,41 +1,29 @@
-use num::Complex;
-use rustc_hash::FxHashMap as HashMap;
+use anyhow::Result;
 use rustc_hash::FxHashSet as HashSet;
 
-type Pos = Complex<isize>;
-type Grid = HashMap<Pos, u32>;
+use crate::grid::{self, Pos};
 
-const DIRECTIONS: [Pos; 4] = [
-    Complex::new(1, 0),
-    Complex::new(0, 1),
-    Complex::new(-1, 0),
-    Complex::new(0, -1),
-];
+type Grid = grid::Grid<u32>;
 
-pub fn parse_input(input: &str) -> (Grid, Vec<Pos>, Vec<Pos>) {
-    let mut grid = HashMap::default();
+pub fn parse_input(input: &str) -> Result<(Grid, Vec<Pos>, Vec<Pos>)> {
     let mut zeros = vec![];
     let mut nines = vec![];
-    for (j, line) in input.lines().enumerate() {
-        for (i, c) in line.chars().enumerate() {
-            let pos = Complex::new(i as isize, j as isize);
-            match c {
-                '0' => zeros.push(pos),
-                '9' => nines.push(pos),
-                _ => (),
-            }
-            grid.insert(pos, c.to_digit(10).unwrap());
+    let grid = Grid::try_parse(input, |pos, c| {
+        match c {
+            '0' => zeros.push(pos),
+            '9' => nines.push(pos),
+            _ => (),
         }
-    }
-    (grid, zeros, nines)
+        c.to_digit(10)
+            .ok_or_else(|| format!("expected a digit, found {c:?}"))
+    })?;
+    Ok((grid, zeros, nines))
 }
 
 fn possible_nexts<'a>(pos: &'a Pos, grid: &'a Grid) -> impl Iterator<Item = Pos> + use<'a> {
     let target_val = grid[pos] + 1;
-    DIRECTIONS
-        .into_iter()
-        .filter(move |dir| grid.get(&(pos + dir)) == Some(&target_val))
-        .map(move |dir| pos + dir)
+    grid.neighbors(*pos)
+        .filter(move |next| grid[next] == target_val)
 }
 
 pub fn part1((grid, zeros, nines): (Grid, Vec<Pos>, Vec<Pos>)) -> usize {
@@ -72,25 +60,14 @@ pub fn part2((grid, zeros, nines): (Grid, Vec<Pos>, Vec<Pos>)) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use indoc::indoc;
-    use num::Zero;
+    use num::{Complex, Zero};
     use pretty_assertions::assert_eq;
 
-    const INPUT: &str = indoc! {
-    "89010123
-    78121874
-    87430965
-    96549874
-    45678903
-    32019012
-    01329801
-    10456732
-    "
-        };
+    const INPUT: &str = include_str!("../fixtures/day10.txt");
 
     #[test]
     fn test_parse_input() {
-        let (grid, zeros, nines) = parse_input(INPUT);
+        let (grid, zeros, nines) = parse_input(INPUT).unwrap();
         assert_eq!(grid[&Complex::zero()], 8);
         assert_eq!(grid[&Complex::new(1, 0)], 9);
         assert_eq!(grid[&Complex::new(0, 1)], 7);
@@ -100,9 +77,23 @@ mod tests {
         assert!(nines.contains(&Complex::new(1, 0)));
     }
 
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("0123\n4.67").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 2: expected a digit, found '.'"
+        );
+        let e = parse_input("0123\n45").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 3: expected a line of width 4, found 2"
+        );
+    }
+
     #[test]
     fn test_possible_next() {
-        let (grid, zeros, _) = parse_input(INPUT);
+        let (grid, zeros, _) = parse_input(INPUT).unwrap();
         assert_eq!(zeros[0], Complex::new(2, 0));
         let nexts = possible_nexts(&zeros[0], &grid);
         assert_eq!(
@@ -116,13 +107,13 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let input = parse_input(INPUT);
+        let input = parse_input(INPUT).unwrap();
         assert_eq!(part1(input), 36);
     }
 
     #[test]
     fn test_part2() {
-        let input = parse_input(INPUT);
+        let input = parse_input(INPUT).unwrap();
         assert_eq!(part2(input), 81);
     }
 }
@@ -1,10 +1,42 @@
+use anyhow::Result;
 use rustc_hash::FxHashMap as HashMap;
 
-pub fn parse_input(input: &str) -> Vec<usize> {
-    input
+use crate::params::{self, parse_param, unknown_param};
+use crate::parsers::parse_at;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    pub blinks_part1: usize,
+    pub blinks_part2: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            blinks_part1: 25,
+            blinks_part2: 75,
+        }
+    }
+}
+
+impl params::Params for Params {
+    const DAY: u8 = 11;
+
+    fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        match name {
+            "blinks_part1" => self.blinks_part1 = parse_param(name, value)?,
+            "blinks_part2" => self.blinks_part2 = parse_param(name, value)?,
+            _ => unknown_param(name)?,
+        }
+        Ok(())
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<usize>> {
+    Ok(input
         .split_whitespace()
-        .map(|x| x.parse().unwrap())
-        .collect()
+        .map(|x| parse_at(input, x))
+        .collect::<Result<_, _>>()?)
 }
 
 fn apply_rules(val: usize) -> Vec<usize> {
@@ -12,7 +44,7 @@ fn apply_rules(val: usize) -> Vec<usize> {
         return vec![1];
     }
     let val_str = val.to_string();
-    if val_str.len() % 2 == 0 {
+    if val_str.len().is_multiple_of(2) {
         return vec![
             val_str[..val_str.len() / 2].parse().unwrap(),
             val_str[val_str.len() / 2..].parse().unwrap(),
@@ -21,9 +53,9 @@ fn apply_rules(val: usize) -> Vec<usize> {
     vec![2024 * val]
 }
 
-pub fn part1(input: Vec<usize>) -> usize {
+pub fn part1((input, params): (Vec<usize>, Params)) -> usize {
     let mut values = input;
-    for _ in 0..25 {
+    for _ in 0..params.blinks_part1 {
         let mut new_vals = vec![];
         for val in values {
             new_vals.extend(apply_rules(val));
@@ -33,13 +65,13 @@ pub fn part1(input: Vec<usize>) -> usize {
     values.len()
 }
 
-pub fn part2(input: Vec<usize>) -> usize {
+pub fn part2((input, params): (Vec<usize>, Params)) -> usize {
     let mut transforms: HashMap<usize, Vec<usize>> = HashMap::default();
     let mut counts: HashMap<usize, usize> = HashMap::default();
     for val in input {
         counts.insert(val, 1);
     }
-    for _ in 0..75 {
+    for _ in 0..params.blinks_part2 {
         let old_counts = counts.clone();
         for (val, count) in old_counts {
             let new_vals = transforms
@@ -63,12 +95,19 @@ mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
 
-    const INPUT: &str = "125 17";
+    const INPUT: &str = include_str!("../fixtures/day11.txt");
 
     #[test]
     fn test_parse_input() {
-        let input = parse_input(INPUT);
-        assert_eq!(input, vec![125, 17]);
+        assert_eq!(parse_input(INPUT).unwrap(), vec![125, 17]);
+    }
+
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("125 17\n-3").unwrap_err();
+        assert!(e
+            .to_string()
+            .starts_with("line 2, column 1: invalid value \"-3\""));
     }
 
     #[test]
@@ -80,7 +119,22 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let input = parse_input(INPUT);
-        assert_eq!(part1(input), 55312);
+        let input = parse_input(INPUT).unwrap();
+        assert_eq!(part1((input, Params::default())), 55312);
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = parse_input(INPUT).unwrap();
+        let params = Params {
+            blinks_part2: 6,
+            ..Default::default()
+        };
+        assert_eq!(part2((input.clone(), params)), 22);
+        let params = Params {
+            blinks_part2: 25,
+            ..Default::default()
+        };
+        assert_eq!(part2((input, params)), 55312);
     }
 }
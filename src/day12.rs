@@ -1,29 +1,23 @@
-use indexmap::{IndexMap, IndexSet};
+use anyhow::Result;
+use indexmap::IndexSet;
 use itertools::Itertools;
 use num::Complex;
-use rustc_hash::FxBuildHasher; // used to pop from the hashmap
+use rustc_hash::FxBuildHasher; // used to pop from the set
 
-type FxIndexMap<K, V> = IndexMap<K, V, FxBuildHasher>;
-type FxIndexSet<K> = IndexSet<K, FxBuildHasher>;
+use crate::grid::{self, Pos};
 
-type Grid = FxIndexMap<Pos, char>;
-type Pos = Complex<isize>;
+type FxIndexSet<K> = IndexSet<K, FxBuildHasher>;
 
-const DIRECTIONS: [Pos; 4] = [
-    Complex::new(1, 0),
-    Complex::new(0, 1),
-    Complex::new(-1, 0),
-    Complex::new(0, -1),
-];
+type Grid = grid::Grid<char>;
 
-pub fn parse_input(input: &str) -> Grid {
-    let mut grid = FxIndexMap::default();
-    for (j, line) in input.lines().enumerate() {
-        for (i, c) in line.chars().enumerate() {
-            grid.insert(Complex::new(i as isize, j as isize), c);
+pub fn parse_input(input: &str) -> Result<Grid> {
+    Ok(Grid::try_parse(input, |_, c| {
+        if c.is_ascii_uppercase() {
+            Ok(c)
+        } else {
+            Err(format!("expected a plant type, found {c:?}"))
         }
-    }
-    grid
+    })?)
 }
 
 fn possible_nexts<'a>(
@@ -31,16 +25,15 @@ fn possible_nexts<'a>(
     grid: &'a Grid,
     target: &'a char,
 ) -> impl Iterator<Item = Pos> + use<'a> {
-    DIRECTIONS
-        .into_iter()
-        .filter(move |dir| grid.get(&(pos + dir)) == Some(target))
-        .map(move |dir| pos + dir)
+    grid.neighbors(*pos)
+        .filter(move |next| grid[next] == *target)
 }
 
 pub fn part1(grid: Grid) -> usize {
-    let mut mutable_grid = grid.clone();
+    let mut remaining: FxIndexSet<Pos> = grid.positions().collect();
     let mut price = 0;
-    while let Some((pos, val)) = mutable_grid.pop() {
+    while let Some(pos) = remaining.pop() {
+        let val = grid[pos];
         let mut seen = FxIndexSet::from_iter([pos]);
         let mut current_perimeter = 0;
         let mut current_area = 0;
@@ -53,7 +46,7 @@ pub fn part1(grid: Grid) -> usize {
                 current_perimeter -= 1;
                 if !seen.contains(&neighbor) {
                     stack.insert(neighbor);
-                    mutable_grid.swap_remove(&neighbor);
+                    remaining.swap_remove(&neighbor);
                 }
             }
         }
@@ -78,7 +71,7 @@ fn compute_sides(positions: &FxIndexSet<Pos>) -> usize {
         }
         nb_sides += 1;
 
-        slice.sort_by(|a, b| a.im.cmp(&b.im));
+        slice.sort_by_key(|p| p.im);
         for (p1, p2) in slice.iter().tuple_windows() {
             if p1.im + 1 < p2.im {
                 nb_sides += 1;
@@ -95,7 +88,7 @@ fn compute_sides(positions: &FxIndexSet<Pos>) -> usize {
         }
         nb_sides += 1;
 
-        slice.sort_by(|a, b| a.im.cmp(&b.im));
+        slice.sort_by_key(|p| p.im);
         for (p1, p2) in slice.iter().tuple_windows() {
             if p1.im + 1 < p2.im {
                 nb_sides += 1;
@@ -113,7 +106,7 @@ fn compute_sides(positions: &FxIndexSet<Pos>) -> usize {
         }
         nb_sides += 1;
 
-        slice.sort_by(|a, b| a.re.cmp(&b.re));
+        slice.sort_by_key(|p| p.re);
         for (p1, p2) in slice.iter().tuple_windows() {
             if p1.re + 1 < p2.re {
                 nb_sides += 1;
@@ -131,7 +124,7 @@ fn compute_sides(positions: &FxIndexSet<Pos>) -> usize {
         }
         nb_sides += 1;
 
-        slice.sort_by(|a, b| a.re.cmp(&b.re));
+        slice.sort_by_key(|p| p.re);
         for (p1, p2) in slice.iter().tuple_windows() {
             if p1.re + 1 < p2.re {
                 nb_sides += 1;
@@ -140,10 +133,12 @@ fn compute_sides(positions: &FxIndexSet<Pos>) -> usize {
     }
     nb_sides
 }
+
 pub fn part2(grid: Grid) -> usize {
-    let mut mutable_grid = grid.clone();
+    let mut remaining: FxIndexSet<Pos> = grid.positions().collect();
     let mut shapes = vec![];
-    while let Some((pos, val)) = mutable_grid.pop() {
+    while let Some(pos) = remaining.pop() {
+        let val = grid[pos];
         let mut seen = FxIndexSet::from_iter([pos]);
         let mut stack = FxIndexSet::from_iter([pos]);
         while let Some(p) = stack.pop() {
@@ -152,7 +147,7 @@ pub fn part2(grid: Grid) -> usize {
                 // current_perimeter -= 1;
                 if !seen.contains(&neighbor) {
                     stack.insert(neighbor);
-                    mutable_grid.swap_remove(&neighbor);
+                    remaining.swap_remove(&neighbor);
                 }
             }
         }
@@ -164,30 +159,32 @@ pub fn part2(grid: Grid) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use indoc::indoc;
     use pretty_assertions::assert_eq;
 
-    const INPUT: &str = indoc! {
-        "RRRRIICCFF
-        RRRRIICCCF
-        VVRRRCCFFF
-        VVRCCCJFFF
-        VVVVCJJCFE
-        VVIVCCJJEE
-        VVIIICJJEE
-        MIIIIIJJEE
-        MIIISIJEEE
-        MMMISSJEEE"
-    };
+    const INPUT: &str = include_str!("../fixtures/day12.txt");
 
     #[test]
     fn test_parse_input() {
-        let grid = parse_input(INPUT);
+        let grid = parse_input(INPUT).unwrap();
         assert_eq!(grid[&Complex::new(0, 0)], 'R');
         assert_eq!(grid[&Complex::new(4, 0)], 'I');
         assert_eq!(grid[&Complex::new(0, 8)], 'M');
     }
 
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("AAB\nA B").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 2: expected a plant type, found ' '"
+        );
+        let e = parse_input("AAB\nAA").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 3: expected a line of width 3, found 2"
+        );
+    }
+
     #[test]
     fn test_part1() {
         // let input = indoc! {
@@ -198,7 +195,7 @@ mod tests {
         //      OOOOO"
         // };
         // assert_eq!(part1(parse_input(input)), 36 * 21 + 4 * 4);
-        let grid = parse_input(INPUT);
+        let grid = parse_input(INPUT).unwrap();
         assert_eq!(part1(grid), 1930);
     }
 
@@ -254,7 +251,7 @@ mod tests {
         //      OOOOO"
         // };
         // assert_eq!(part1(parse_input(input)), 36 * 21 + 4 * 4);
-        let grid = parse_input(INPUT);
+        let grid = parse_input(INPUT).unwrap();
         // &grid);
         assert_eq!(part2(grid), 1206);
     }
@@ -1,36 +1,41 @@
 use std::str::FromStr;
 
+use anyhow::Result;
 use winnow::{
     ascii::{digit1, newline},
     combinator::{delimited, preceded, terminated},
     token::one_of,
-    PResult, Parser,
+    ModalResult, Parser,
 };
 
-fn x(input: &mut &str) -> PResult<usize> {
+use crate::parsers::ParseError;
+
+type Coords = (usize, usize);
+
+fn x(input: &mut &str) -> ModalResult<usize> {
     delimited(('X', one_of(['+', '='])), digit1, ", ")
         .parse_to()
         .parse_next(input)
 }
-fn y(input: &mut &str) -> PResult<usize> {
+fn y(input: &mut &str) -> ModalResult<usize> {
     preceded(('Y', one_of(['+', '='])), digit1)
         .parse_to()
         .parse_next(input)
 }
 
-fn button_a(input: &mut &str) -> PResult<(usize, usize)> {
+fn button_a(input: &mut &str) -> ModalResult<Coords> {
     preceded("Button A: ", (x, y)).parse_next(input)
 }
 
-fn button_b(input: &mut &str) -> PResult<(usize, usize)> {
+fn button_b(input: &mut &str) -> ModalResult<Coords> {
     preceded("Button B: ", (x, y)).parse_next(input)
 }
 
-fn prize(input: &mut &str) -> PResult<(usize, usize)> {
+fn prize(input: &mut &str) -> ModalResult<Coords> {
     preceded("Prize: ", (x, y)).parse_next(input)
 }
 
-fn parse_block(input: &mut &str) -> PResult<((usize, usize), (usize, usize), (usize, usize))> {
+fn parse_block(input: &mut &str) -> ModalResult<(Coords, Coords, Coords)> {
     (
         terminated(button_a, newline),
         terminated(button_b, newline),
@@ -77,24 +82,35 @@ impl Machine {
     }
 }
 
+/// Parse the machine described in `block`, a slice of `input`
+fn parse_machine(input: &str, block: &str) -> Result<Machine, ParseError> {
+    let mut rest = block;
+    match parse_block(&mut rest) {
+        Ok((a, b, prize)) => Ok(Machine::new(a, b, prize)),
+        Err(_) => Err(ParseError::at(
+            input,
+            rest,
+            format!(
+                "could not parse machine at {:?}",
+                rest.lines().next().unwrap_or_default()
+            ),
+        )),
+    }
+}
+
 impl FromStr for Machine {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut input = s;
-        if let Ok((a, b, prize)) = parse_block(&mut input) {
-            Ok(Machine::new(a, b, prize))
-        } else {
-            Err(format!("Could not parse block {s}"))
-        }
+        parse_machine(s, s)
     }
 }
 
-pub fn parse_input(input: &str) -> Vec<Machine> {
-    input
+pub fn parse_input(input: &str) -> Result<Vec<Machine>> {
+    Ok(input
         .split("\n\n")
-        .map(|block| block.parse().unwrap())
-        .collect()
+        .map(|block| parse_machine(input, block))
+        .collect::<Result<_, _>>()?)
 }
 
 pub fn part1(machines: Vec<Machine>) -> isize {
@@ -121,32 +137,34 @@ mod tests {
     use indoc::indoc;
     use pretty_assertions::assert_eq;
 
-    const INPUT: &str = indoc! {
-        "Button A: X+94, Y+34
-        Button B: X+22, Y+67
-        Prize: X=8400, Y=5400
-
-        Button A: X+26, Y+66
-        Button B: X+67, Y+21
-        Prize: X=12748, Y=12176
-
-        Button A: X+17, Y+86
-        Button B: X+84, Y+37
-        Prize: X=7870, Y=6450
-
-        Button A: X+69, Y+23
-        Button B: X+27, Y+71
-        Prize: X=18641, Y=10279"
-    };
+    const INPUT: &str = include_str!("../fixtures/day13.txt");
 
     #[test]
     fn test_parse_input() {
-        let machines = parse_input(INPUT);
+        let machines = parse_input(INPUT).unwrap();
         assert_eq!(machines[0], Machine::new((94, 34), (22, 67), (8400, 5400)));
     }
+    #[test]
+    fn test_parse_input_error() {
+        let input = indoc! {
+            "Button A: X+94, Y+34
+            Button B: X+22, Y+67
+            Prize: X=8400, Y=5400
+
+            Button A: X+26, Y+66
+            Button B: X+67 Y+21
+            Prize: X=12748, Y=12176"
+        };
+        let e = parse_input(input).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 6, column 15: could not parse machine at \" Y+21\""
+        );
+    }
+
     #[test]
     fn test_part1() {
-        let machines = parse_input(INPUT);
+        let machines = parse_input(INPUT).unwrap();
         assert_eq!(part1(machines), 480);
     }
 }
@@ -1,25 +1,63 @@
 use rustc_hash::FxHashSet as HashSet;
 use std::str::FromStr;
 
+use anyhow::Result;
 use num::Complex;
 use winnow::{
     ascii::digit1,
     combinator::{preceded, separated_pair},
-    PResult, Parser,
+    ModalResult, Parser,
 };
 
-use crate::parsers::neg_num;
+use crate::params::{self, parse_param, unknown_param};
+use crate::parsers::{neg_num, ParseError};
 
 type Pos = Complex<isize>;
 
-fn p(input: &mut &str) -> PResult<(isize, isize)> {
+/// The size of the room, the number of steps of part 1, and whether to print the picture found
+/// in part 2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    pub width: isize,
+    pub height: isize,
+    pub steps: isize,
+    pub render: bool,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            width: 101,
+            height: 103,
+            steps: 100,
+            render: false,
+        }
+    }
+}
+
+impl params::Params for Params {
+    const DAY: u8 = 14;
+
+    fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        match name {
+            "width" => self.width = parse_param(name, value)?,
+            "height" => self.height = parse_param(name, value)?,
+            "steps" => self.steps = parse_param(name, value)?,
+            "render" => self.render = parse_param(name, value)?,
+            _ => unknown_param(name)?,
+        }
+        Ok(())
+    }
+}
+
+fn p(input: &mut &str) -> ModalResult<(isize, isize)> {
     preceded(
         "p=",
         separated_pair(digit1.parse_to(), ',', digit1.parse_to()),
     )
     .parse_next(input)
 }
-fn v(input: &mut &str) -> PResult<(isize, isize)> {
+fn v(input: &mut &str) -> ModalResult<(isize, isize)> {
     preceded(
         " v=",
         separated_pair(neg_num::<isize>, ',', neg_num::<isize>),
@@ -46,28 +84,34 @@ impl Robot {
 }
 
 impl FromStr for Robot {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut input = s;
-        if let Ok((p, v)) = (p, v).parse_next(&mut input) {
-            Ok(Robot::new(Complex::new(p.0, p.1), Complex::new(v.0, v.1)))
-        } else {
-            Err(format!("Could not parse line {s}"))
+        match (p, v).parse_next(&mut input) {
+            Ok((p, v)) => Ok(Robot::new(Complex::new(p.0, p.1), Complex::new(v.0, v.1))),
+            Err(_) => Err(ParseError::at(
+                s,
+                input,
+                format!("could not parse robot at {input:?}"),
+            )),
         }
     }
 }
 
-pub fn parse_input(input: &str) -> Vec<Robot> {
-    input.lines().map(|l| l.parse().unwrap()).collect()
+pub fn parse_input(input: &str) -> Result<Vec<Robot>> {
+    Ok(input
+        .lines()
+        .map(|l| l.parse().map_err(|e: ParseError| e.rebase(input, l)))
+        .collect::<Result<_, _>>()?)
 }
 
-pub fn part1(input: Vec<Robot>) -> usize {
+pub fn part1((input, params): (Vec<Robot>, Params)) -> usize {
     let mut robots = input.clone();
-    let max_x = 101;
-    let max_y = 103;
+    let max_x = params.width;
+    let max_y = params.height;
     for robot in &mut robots {
-        robot.move_n_steps(100, max_x, max_y);
+        robot.move_n_steps(params.steps, max_x, max_y);
     }
     let top_left = robots
         .iter()
@@ -88,9 +132,9 @@ pub fn part1(input: Vec<Robot>) -> usize {
     top_left * top_right * bottom_left * bottom_right
 }
 
-fn _print_grid(grid: &HashSet<Complex<isize>>, max_x: isize, max_y: isize) {
-    for i in 0..max_x {
-        for j in 0..max_y {
+fn print_grid(grid: &HashSet<Complex<isize>>, max_x: isize, max_y: isize) {
+    for j in 0..max_y {
+        for i in 0..max_x {
             if grid.contains(&Complex::new(i, j)) {
                 print!("X")
             } else {
@@ -102,69 +146,68 @@ fn _print_grid(grid: &HashSet<Complex<isize>>, max_x: isize, max_y: isize) {
     println!()
 }
 
-pub fn part2(input: Vec<Robot>) -> isize {
+/// How spread out the robots are: the sum of the variances of their coordinates, times the
+/// number of robots squared to stay in integers. It drops when they gather to draw a picture.
+fn spread(robots: &[Robot]) -> isize {
+    let n = robots.len() as isize;
+    let variance = |coordinates: &dyn Fn(&Robot) -> isize| {
+        let sum: isize = robots.iter().map(coordinates).sum();
+        let sum_squares: isize = robots.iter().map(|r| coordinates(r).pow(2)).sum();
+        n * sum_squares - sum * sum
+    };
+    variance(&|r| r.p.re) + variance(&|r| r.p.im)
+}
+
+/// The robots positions repeat every `max_x * max_y` steps, so we look for the step where they
+/// are the least spread out in that period
+pub fn part2((input, params): (Vec<Robot>, Params)) -> isize {
     let mut robots = input.clone();
-    let max_x = 101;
-    let max_y = 103;
-    let steps = 7623;
-    for robot in &mut robots {
-        robot.move_n_steps(steps, max_x, max_y);
+    let max_x = params.width;
+    let max_y = params.height;
+    let mut best = (spread(&robots), 0);
+    for steps in 1..max_x * max_y {
+        for robot in &mut robots {
+            robot.move_n_steps(1, max_x, max_y);
+        }
+        best = best.min((spread(&robots), steps));
+    }
+    let steps = best.1;
+    if params.render {
+        let mut robots = input;
+        for robot in &mut robots {
+            robot.move_n_steps(steps, max_x, max_y);
+        }
+        let grid: HashSet<Complex<isize>> = HashSet::from_iter(robots.iter().map(|r| r.p));
+        print_grid(&grid, max_x, max_y);
     }
-    // let grid: HashSet<Complex<isize>> = HashSet::from_iter(robots.iter().map(|r| r.p));
-    // _print_grid(&grid, max_x, max_y);
-    // loop {
-    //     println!("{steps}");
-    //     for robot in &mut robots {
-    //         robot.move_n_steps(1, max_x, max_y);
-    //     }
-    //     // let's look for a lot of robots on the same line, let's say more than 20
-    //     let mut d = 0;
-    //     for i in 0..max_y {
-    //         let grid: HashSet<Complex<isize>> = HashSet::from_iter(robots.iter().map(|r| r.p));
-    //         let c = grid.iter().filter(|p| p.re == i).count();
-    //         if c > 20 {
-    //             d += 1;
-    //         }
-    //     }
-    //     if d > 4 {
-    //         _print_grid(&grid, max_x, max_y);
-    //     }
-    //     steps += 1;
-    // }
-
     steps
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use indoc::indoc;
     use pretty_assertions::assert_eq;
 
-    const INPUT: &str = indoc! {
-        "p=0,4 v=3,-3
-        p=6,3 v=-1,-3
-        p=10,3 v=-1,2
-        p=2,0 v=2,-1
-        p=0,0 v=1,3
-        p=3,0 v=-2,-2
-        p=7,6 v=-1,-3
-        p=3,0 v=-1,-2
-        p=9,3 v=2,3
-        p=7,3 v=-1,2
-        p=2,4 v=2,-3
-        p=9,5 v=-3,-3"
-    };
+    const INPUT: &str = include_str!("../fixtures/day14.txt");
 
     #[test]
     fn test_parse_input() {
-        let robots = parse_input(INPUT);
+        let robots = parse_input(INPUT).unwrap();
         assert_eq!(
             robots[0],
             Robot::new(Complex::new(0, 4), Complex::new(3, -3))
         );
     }
 
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("p=0,4 v=3,-3\np=6,3 w=-1,-3").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 6: could not parse robot at \" w=-1,-3\""
+        );
+    }
+
     #[test]
     fn test_move() {
         let mut robot: Robot = "p=2,4 v=2,-3".parse().unwrap();
@@ -177,7 +220,33 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let robots = parse_input(INPUT);
-        assert_eq!(part1(robots), 12);
+        let robots = parse_input(INPUT).unwrap();
+        let params = Params {
+            width: 11,
+            height: 7,
+            ..Default::default()
+        };
+        assert_eq!(part1((robots, params)), 12);
+    }
+
+    #[test]
+    fn test_part2() {
+        // robots with random velocities that all gather in a 10x10 square after 1234 steps
+        let params = Params::default();
+        let mut seed: isize = 2024;
+        let mut random = |max: isize| {
+            seed = (seed * 1103515245 + 12345).rem_euclid(1 << 31);
+            (seed >> 8) % max
+        };
+        let robots: Vec<Robot> = (0..200)
+            .map(|_| {
+                let p = Complex::new(40 + random(10), 50 + random(10));
+                let v = Complex::new(random(201) - 100, random(201) - 100);
+                let mut robot = Robot::new(p, -v);
+                robot.move_n_steps(1234, params.width, params.height);
+                Robot::new(robot.p, v)
+            })
+            .collect();
+        assert_eq!(part2((robots, params)), 1234);
     }
 }
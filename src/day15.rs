@@ -1,10 +1,29 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
+use std::fmt::Display;
 
-use num::{Complex, Zero};
+use anyhow::Result;
+use num::Complex;
 
-type Pos = Complex<isize>;
-type Grid = HashMap<Pos, Element>;
-type Directions = Vec<Direction>;
+use crate::grid::{self, DenseGrid, Pos};
+use crate::parsers::ParseError;
+
+mod history;
+mod replay;
+mod solver;
+
+pub use history::{Delta, Simulation};
+pub use replay::{export_frames, render, Frame, FrameFormat, Replay, Tile};
+pub use solver::{solve, Budget};
+
+pub type Grid = grid::DenseGrid<Element>;
+pub type Directions = Vec<Direction>;
+
+/// A map in which the robot pushes boxes around
+pub trait Warehouse {
+    /// Move the robot at `robot` towards `direction`, pushing the boxes in its way. Returns
+    /// where the robot ends up, `None` if it is blocked.
+    fn push(&mut self, robot: Pos, direction: Direction) -> Option<Pos>;
+}
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum Direction {
@@ -26,6 +45,27 @@ impl Direction {
     }
 }
 
+/// A tile of a warehouse, whose boxes cover a rectangle of tiles
+pub trait Footprint: Copy + PartialEq {
+    const EMPTY: Self;
+    const WALL: Self;
+
+    /// The offset from this tile to the top left tile of its box, `None` if it is not a box
+    fn box_offset(&self) -> Option<Pos>;
+
+    /// The tiles of a box, with their offsets from its top left tile
+    fn box_tiles() -> Vec<(Pos, Self)>;
+
+    /// The width and height of a box
+    fn size() -> Pos {
+        Self::box_tiles()
+            .into_iter()
+            .fold(Complex::new(1, 1), |size, (offset, _)| {
+                Complex::new(size.re.max(offset.re + 1), size.im.max(offset.im + 1))
+            })
+    }
+}
+
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum Element {
     Empty,
@@ -33,75 +73,185 @@ pub enum Element {
     Box,
 }
 
-pub fn parse_input(input: &str) -> (Pos, Grid, Directions) {
-    let (grid_str, instructions) = input.split_once("\n\n").unwrap();
-    let mut grid = HashMap::default();
-    let mut robot_pos = Complex::zero();
-    for (j, line) in grid_str.lines().enumerate() {
-        for (i, c) in line.chars().enumerate() {
-            let pos = Complex::new(i as isize, j as isize);
-            let elem = match c {
-                '#' => Element::Wall,
-                'O' => Element::Box,
-                '.' => Element::Empty,
-                '@' => {
-                    robot_pos = pos;
-                    Element::Empty
-                }
-                c => panic!("Unexpected character {}", c),
-            };
-            grid.insert(pos, elem);
+impl Display for Element {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Element::Empty => write!(f, "."),
+            Element::Wall => write!(f, "#"),
+            Element::Box => write!(f, "O"),
         }
     }
+}
+
+impl Footprint for Element {
+    const EMPTY: Self = Element::Empty;
+    const WALL: Self = Element::Wall;
+
+    fn box_offset(&self) -> Option<Pos> {
+        (*self == Element::Box).then_some(Complex::new(0, 0))
+    }
+
+    fn box_tiles() -> Vec<(Pos, Self)> {
+        vec![(Complex::new(0, 0), Element::Box)]
+    }
+}
+
+/// Parse the moves of `instructions`, a slice of `input`
+fn parse_directions(input: &str, instructions: &str) -> Result<Directions, ParseError> {
+    instructions
+        .char_indices()
+        .filter(|(_, c)| *c != '\n')
+        .map(|(i, c)| match c {
+            'v' => Ok(Direction::Down),
+            '<' => Ok(Direction::Left),
+            '>' => Ok(Direction::Right),
+            '^' => Ok(Direction::Up),
+            c => Err(ParseError::at(
+                input,
+                &instructions[i..],
+                format!("unexpected move {c:?}"),
+            )),
+        })
+        .collect()
+}
+
+pub fn parse_input(input: &str) -> Result<(Pos, Grid, Directions)> {
+    let (grid_str, instructions) = input.split_once("\n\n").ok_or_else(|| {
+        ParseError::at_end(
+            input,
+            "expected an empty line between the map and the moves",
+        )
+    })?;
+    let mut robot_pos = None;
+    let grid = Grid::try_parse(grid_str, |pos, c| match c {
+        '#' => Ok(Element::Wall),
+        'O' => Ok(Element::Box),
+        '.' => Ok(Element::Empty),
+        '@' => {
+            robot_pos = Some(pos);
+            Ok(Element::Empty)
+        }
+        c => Err(format!("unexpected character {c:?}")),
+    })?;
+    let robot_pos =
+        robot_pos.ok_or_else(|| ParseError::at(input, instructions, "no robot '@' in the map"))?;
+    Ok((robot_pos, grid, parse_directions(input, instructions)?))
+}
 
-    let instructions = instructions.replace("\n", "");
-    let directions = instructions
-        .chars()
-        .map(|c| match c {
-            'v' => Direction::Down,
-            '<' => Direction::Left,
-            '>' => Direction::Right,
-            '^' => Direction::Up,
-            c => panic!("Unexpected character {}", c),
+/// Scale the map so that each box of `grid` becomes a box of `T`, and each other tile as many
+/// tiles as a box of `T` covers. The robot stays on the top left tile of its scaled tile.
+pub fn scale<T: Footprint>(robot_pos: Pos, grid: &Grid) -> (Pos, DenseGrid<T>) {
+    let size = T::size();
+    let grid = grid
+        .iter()
+        .flat_map(|(pos, e)| {
+            let origin = Complex::new(pos.re * size.re, pos.im * size.im);
+            let tiles = match e {
+                Element::Box => T::box_tiles(),
+                Element::Wall => tiles_of_size(size, T::WALL),
+                Element::Empty => tiles_of_size(size, T::EMPTY),
+            };
+            tiles
+                .into_iter()
+                .map(move |(offset, tile)| (origin + offset, tile))
         })
         .collect();
-    (robot_pos, grid, directions)
+    let robot_pos = Complex::new(robot_pos.re * size.re, robot_pos.im * size.im);
+    (robot_pos, grid)
+}
+
+fn tiles_of_size<T: Copy>(size: Pos, tile: T) -> Vec<(Pos, T)> {
+    (0..size.im)
+        .flat_map(|j| (0..size.re).map(move |i| (Complex::new(i, j), tile)))
+        .collect()
+}
+
+/// Give the top left tile of each box that moves when the robot at `pos` goes towards
+/// `direction`, `None` if a wall blocks them
+fn boxes_to_move<T: Footprint>(
+    pos: &Pos,
+    direction: &Direction,
+    grid: &DenseGrid<T>,
+) -> Option<HashSet<Pos>> {
+    boxes_to_move_with(pos, direction, |pos| grid[pos])
+}
+
+/// [`boxes_to_move`] with the tiles given by `tile_at`, for maps not stored as a grid
+fn boxes_to_move_with<T: Footprint>(
+    pos: &Pos,
+    direction: &Direction,
+    tile_at: impl Fn(&Pos) -> T,
+) -> Option<HashSet<Pos>> {
+    let mut boxes = HashSet::new();
+    let mut to_check = vec![pos + direction.val()];
+    while let Some(pos) = to_check.pop() {
+        let tile = tile_at(&pos);
+        if tile == T::WALL {
+            return None;
+        }
+        let Some(offset) = tile.box_offset() else {
+            continue;
+        };
+        let origin = pos + offset;
+        if boxes.insert(origin) {
+            // the tiles in front of the box, the ones within the box move with it
+            let tiles: Vec<Pos> = T::box_tiles()
+                .into_iter()
+                .map(|(offset, _)| origin + offset)
+                .collect();
+            to_check.extend(
+                tiles
+                    .iter()
+                    .map(|tile| tile + direction.val())
+                    .filter(|next| !tiles.contains(next)),
+            );
+        }
+    }
+    Some(boxes)
 }
 
-fn can_move(pos: &Pos, direction: &Direction, grid: &Grid) -> Option<Pos> {
-    let new_pos = pos + direction.val();
-    match grid[&new_pos] {
-        Element::Empty => Some(new_pos),
-        Element::Wall => None,
-        Element::Box => can_move(&new_pos, direction, grid),
+/// Move the boxes whose top left tiles are `boxes` one tile towards `direction`
+fn update_grid<T: Footprint>(boxes: &HashSet<Pos>, direction: &Direction, grid: &mut DenseGrid<T>) {
+    let tiles = T::box_tiles();
+    for origin in boxes {
+        for (offset, _) in &tiles {
+            grid[origin + offset] = T::EMPTY;
+        }
+    }
+    for origin in boxes {
+        for (offset, tile) in &tiles {
+            grid[origin + offset + direction.val()] = *tile;
+        }
     }
 }
 
-/// update the grid from final_pos to initial_pos
-fn update_grid(initial_pos: &Pos, final_pos: &Pos, direction: &Direction, grid: &mut Grid) {
-    let mut current_pos = *final_pos;
-    while &current_pos != initial_pos {
-        let new_val = grid[&(current_pos - direction.val())];
-        grid.entry(current_pos).and_modify(|val| *val = new_val);
-        current_pos -= direction.val();
+impl<T: Footprint> Warehouse for DenseGrid<T> {
+    fn push(&mut self, robot: Pos, direction: Direction) -> Option<Pos> {
+        let boxes = boxes_to_move(&robot, &direction, self)?;
+        update_grid(&boxes, &direction, self);
+        Some(robot + direction.val())
     }
 }
 
-pub fn part1((robot_pos, grid, directions): (Pos, Grid, Directions)) -> isize {
+/// Apply every move, then sum the GPS coordinates of the top left tiles of the boxes
+pub fn simulate<T: Footprint>(robot_pos: Pos, grid: DenseGrid<T>, directions: Directions) -> isize {
     let mut current_pos = robot_pos;
     let mut grid = grid;
     for direction in directions {
-        if let Some(final_pos) = can_move(&current_pos, &direction, &grid) {
-            update_grid(&current_pos, &final_pos, &direction, &mut grid);
-            current_pos += direction.val();
+        if let Some(pos) = grid.push(current_pos, direction) {
+            current_pos = pos;
         }
     }
-    grid.into_iter()
-        .filter(|(_, e)| *e == Element::Box)
+    grid.iter()
+        .filter(|(_, e)| e.box_offset() == Some(Complex::new(0, 0)))
         .map(|(p, _)| p.re + 100 * p.im)
         .sum()
 }
 
+pub fn part1((robot_pos, grid, directions): (Pos, Grid, Directions)) -> isize {
+    simulate(robot_pos, grid, directions)
+}
+
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum ElementLarge {
     Empty,
@@ -110,192 +260,110 @@ pub enum ElementLarge {
     BoxRight,
 }
 
-type Grid2 = HashMap<Pos, ElementLarge>;
-
-pub fn parse_input_p2(input: &str) -> (Pos, Grid2, Directions) {
-    let (grid_str, instructions) = input.split_once("\n\n").unwrap();
-    let mut grid = HashMap::default();
-    let mut robot_pos = Complex::zero();
-    for (j, line) in grid_str.lines().enumerate() {
-        for (i, c) in line.chars().enumerate() {
-            let pos_left = Complex::new(2 * i as isize, j as isize);
-            let pos_right = Complex::new((2 * i + 1) as isize, j as isize);
-            let (elem1, elem2) = match c {
-                '#' => (ElementLarge::Wall, ElementLarge::Wall),
-                'O' => (ElementLarge::BoxLeft, ElementLarge::BoxRight),
-                '.' => (ElementLarge::Empty, ElementLarge::Empty),
-                '@' => {
-                    robot_pos = pos_left;
-                    (ElementLarge::Empty, ElementLarge::Empty)
-                }
-                c => panic!("Unexpected character {}", c),
-            };
-            grid.insert(pos_left, elem1);
-            grid.insert(pos_right, elem2);
+impl Display for ElementLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ElementLarge::Empty => write!(f, "."),
+            ElementLarge::Wall => write!(f, "#"),
+            ElementLarge::BoxLeft => write!(f, "["),
+            ElementLarge::BoxRight => write!(f, "]"),
         }
     }
-
-    let instructions = instructions.replace("\n", "");
-    let directions = instructions
-        .chars()
-        .map(|c| match c {
-            'v' => Direction::Down,
-            '<' => Direction::Left,
-            '>' => Direction::Right,
-            '^' => Direction::Up,
-            c => panic!("Unexpected character {}", c),
-        })
-        .collect();
-    (robot_pos, grid, directions)
 }
 
-fn _print_grid(grid: &Grid2, robot_pos: &Pos) {
-    if grid.is_empty() {
-        return;
-    }
-    let x_max = grid.iter().map(|(p, _)| p.re).max().unwrap();
-    let y_max = grid.iter().map(|(p, _)| p.im).max().unwrap();
-    for j in 0..=y_max {
-        for i in 0..=x_max {
-            let p = Complex::new(i, j);
-            if &p == robot_pos {
-                print!("@");
-                continue;
-            }
-            let c = match grid.get(&p) {
-                None => 'x',
-                Some(ElementLarge::Empty) => '.',
-                Some(ElementLarge::Wall) => '#',
-                Some(ElementLarge::BoxLeft) => '[',
-                Some(ElementLarge::BoxRight) => ']',
-            };
-            print!("{}", c);
+impl Footprint for ElementLarge {
+    const EMPTY: Self = ElementLarge::Empty;
+    const WALL: Self = ElementLarge::Wall;
+
+    fn box_offset(&self) -> Option<Pos> {
+        match self {
+            ElementLarge::BoxLeft => Some(Complex::new(0, 0)),
+            ElementLarge::BoxRight => Some(Complex::new(-1, 0)),
+            _ => None,
         }
-        println!();
     }
-    println!();
-}
 
-/// Give the Left side of each box that can move
-fn boxes_to_move(pos: &Pos, direction: &Direction, grid: &Grid2) -> Option<HashSet<Pos>> {
-    let new_pos = pos + direction.val();
-    match (grid[&new_pos], direction) {
-        (ElementLarge::Empty, _) => Some(HashSet::default()),
-        (ElementLarge::Wall, _) => None,
-        (e, Direction::Left | Direction::Right) => {
-            if let Some(mut r) = boxes_to_move(&new_pos, direction, grid) {
-                if e == ElementLarge::BoxLeft {
-                    r.insert(new_pos);
-                }
-                Some(r)
-            } else {
-                None
-            }
-        }
-        // the difficulty arises when we move a block up or down
-        (ElementLarge::BoxLeft, _) => {
-            let can_move_left_side = boxes_to_move(&new_pos, direction, grid);
-            let can_move_right_side =
-                boxes_to_move(&(new_pos + Direction::Right.val()), direction, grid);
-            match (can_move_left_side, can_move_right_side) {
-                (None, _) => None,
-                (_, None) => None,
-                (Some(mut vl), Some(vr)) => {
-                    vl.extend(vr);
-                    vl.insert(new_pos);
-                    Some(vl)
-                }
-            }
-        }
-        (ElementLarge::BoxRight, _) => {
-            let can_move_left_side =
-                boxes_to_move(&(new_pos + Direction::Left.val()), direction, grid);
-            let can_move_right_side = boxes_to_move(&(new_pos), direction, grid);
-            match (can_move_left_side, can_move_right_side) {
-                (None, _) => None,
-                (_, None) => None,
-                (Some(mut vl), Some(vr)) => {
-                    vl.extend(vr);
-                    vl.insert(new_pos + Direction::Left.val());
-                    Some(vl)
-                }
-            }
-        }
+    fn box_tiles() -> Vec<(Pos, Self)> {
+        vec![
+            (Complex::new(0, 0), ElementLarge::BoxLeft),
+            (Complex::new(1, 0), ElementLarge::BoxRight),
+        ]
     }
 }
 
-/// compute the new column after being pushed
-fn build_mini_grid(boxes_to_move: &HashSet<Pos>, direction: &Direction) -> Grid2 {
-    let mut mini_grid = Grid2::default();
-    for box_left in boxes_to_move {
-        mini_grid.insert(*box_left + direction.val(), ElementLarge::BoxLeft);
-        mini_grid.insert(
-            *box_left + direction.val() + Direction::Right.val(),
-            ElementLarge::BoxRight,
-        );
-    }
-    mini_grid
+pub type Grid2 = grid::DenseGrid<ElementLarge>;
+
+pub fn parse_input_p2(input: &str) -> Result<(Pos, Grid2, Directions)> {
+    let (robot_pos, grid, directions) = parse_input(input)?;
+    // every tile is twice as wide, except the robot
+    let (robot_pos, grid) = scale(robot_pos, &grid);
+    Ok((robot_pos, grid, directions))
+}
+
+pub fn part2((robot_pos, grid, directions): (Pos, Grid2, Directions)) -> isize {
+    simulate(robot_pos, grid, directions)
+}
+
+/// A tile of a warehouse with `WIDTH` x `HEIGHT` boxes, to experiment beyond the puzzle
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum Block<const WIDTH: usize, const HEIGHT: usize> {
+    Empty,
+    Wall,
+    /// The tile at `column` and `row` within its box
+    Box {
+        column: usize,
+        row: usize,
+    },
 }
 
-/// update the grid from final_pos to initial_pos
-fn update_grid_p2(boxes_left_to_move: &HashSet<Pos>, direction: &Direction, grid: &mut Grid2) {
-    let mini_grid: Grid2 = build_mini_grid(boxes_left_to_move, direction);
-    for (pos, elem) in mini_grid.clone() {
-        grid.insert(pos, elem);
-        if !mini_grid.contains_key(&(pos - direction.val())) {
-            grid.insert(pos - direction.val(), ElementLarge::Empty);
+impl<const WIDTH: usize, const HEIGHT: usize> Display for Block<WIDTH, HEIGHT> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Block::Empty => write!(f, "."),
+            Block::Wall => write!(f, "#"),
+            Block::Box { .. } if WIDTH == 1 => write!(f, "O"),
+            Block::Box { column: 0, .. } => write!(f, "["),
+            Block::Box { column, .. } if *column == WIDTH - 1 => write!(f, "]"),
+            Block::Box { .. } => write!(f, "="),
         }
     }
 }
 
-pub fn part2((robot_pos, grid, directions): (Pos, Grid2, Directions)) -> isize {
-    let mut current_pos = robot_pos;
-    let mut grid = grid;
-    for direction in directions {
-        if let Some(boxes_left_to_move) = boxes_to_move(&current_pos, &direction, &grid) {
-            update_grid_p2(&boxes_left_to_move, &direction, &mut grid);
-            current_pos += direction.val();
+impl<const WIDTH: usize, const HEIGHT: usize> Footprint for Block<WIDTH, HEIGHT> {
+    const EMPTY: Self = Block::Empty;
+    const WALL: Self = Block::Wall;
+
+    fn box_offset(&self) -> Option<Pos> {
+        match self {
+            Block::Box { column, row } => Some(-Complex::new(*column as isize, *row as isize)),
+            _ => None,
         }
     }
-    grid.into_iter()
-        .filter(|(_, e)| *e == ElementLarge::BoxLeft)
-        .map(|(p, _)| p.re + 100 * p.im)
-        .sum()
+
+    fn box_tiles() -> Vec<(Pos, Self)> {
+        (0..HEIGHT)
+            .flat_map(|row| {
+                (0..WIDTH).map(move |column| {
+                    let offset = Complex::new(column as isize, row as isize);
+                    (offset, Block::Box { column, row })
+                })
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use indoc::indoc;
+    use num::Zero;
     use pretty_assertions::assert_eq;
 
-    const INPUT: &str = indoc! {
-        "##########
-        #..O..O.O#
-        #......O.#
-        #.OO..O.O#
-        #..O@..O.#
-        #O#..O...#
-        #O..O..O.#
-        #.OO.O.OO#
-        #....O...#
-        ##########
-
-        <vv>^<v^>v>^vv^v>v<>v^v<v<^vv<<<^><<><>>v<vvv<>^v^>^<<<><<v<<<v^vv^v>^
-        vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v
-        ><>vv>v^v^<>><>>>><^^>vv>v<^^^>>v^v^<^^>v^^>v^<^v>v<>>v^v^<v>v^^<^^vv<
-        <<v<^>>^^^^>>>v^<>vvv^><v<<<>^^^vv^<vvv>^>v<^^^^v<>^>vvvv><>>v^<<^^^^^
-        ^><^><>>><>^^<<^^v>>><^<v>^<vv>>v>>>^v><>^v><<<<v>>v<v<v>vvv>^<><<>^><
-        ^>><>^v<><^vvv<^^<><v<<<<<><^v<<<><<<^^<v<^^^><^>>^<v^><<<^>>^v<v^v<v^
-        >^>>^v>vv>^<<^v<>><<><<v<<v><>v<^vv<<<>^^v^>^^>>><<^v>>v^v><^^>>^<>vv^
-        <><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>
-        ^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>
-        v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^"
-    };
+    const INPUT: &str = include_str!("../fixtures/day15.txt");
 
     #[test]
     fn test_parse_input() {
-        let (robot_pos, grid, directions) = parse_input(INPUT);
+        let (robot_pos, grid, directions) = parse_input(INPUT).unwrap();
         assert_eq!(robot_pos, Complex::new(4, 4));
         assert_eq!(grid[&robot_pos], Element::Empty);
         assert_eq!(grid[&Complex::zero()], Element::Wall);
@@ -304,53 +372,69 @@ mod tests {
         assert_eq!(directions[0], Direction::Left);
     }
 
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("####\n#.O#\n####\n\n<<>").unwrap_err();
+        assert_eq!(e.to_string(), "line 5, column 1: no robot '@' in the map");
+        let e = parse_input("####\n#@O#\n####\n\n<<>\n>v<x").unwrap_err();
+        assert_eq!(e.to_string(), "line 6, column 4: unexpected move 'x'");
+        let e = parse_input("####\n#@O#\n####\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 4, column 1: expected an empty line between the map and the moves"
+        );
+    }
+
     #[test]
     fn test_can_move() {
-        let (robot_pos, grid, _directions) = parse_input(INPUT);
+        let (robot_pos, grid, _directions) = parse_input(INPUT).unwrap();
         // the robot in (1, 1) cannot move up
-        assert_eq!(can_move(&Complex::new(1, 1), &Direction::Up, &grid), None);
+        assert_eq!(
+            boxes_to_move(&Complex::new(1, 1), &Direction::Up, &grid),
+            None
+        );
 
         // the robot can move up
         assert_eq!(
-            can_move(&robot_pos, &Direction::Up, &grid),
-            Some(robot_pos + Direction::Up.val())
+            boxes_to_move(&robot_pos, &Direction::Up, &grid),
+            Some(HashSet::new())
         );
         // the robot can move left, and it will move a box
         assert_eq!(
-            can_move(&robot_pos, &Direction::Left, &grid),
-            Some(robot_pos + Direction::Left.val() * 2)
+            boxes_to_move(&robot_pos, &Direction::Left, &grid),
+            Some(HashSet::from([robot_pos + Direction::Left.val()]))
         );
         // if the robot is in (4, 7), it can move left, and it will move 2 boxes
         assert_eq!(
-            can_move(&Complex::new(4, 7), &Direction::Left, &grid),
-            Some(Complex::new(4, 7) + Direction::Left.val() * 3)
+            boxes_to_move(&Complex::new(4, 7), &Direction::Left, &grid),
+            Some(HashSet::from([Complex::new(3, 7), Complex::new(2, 7)]))
         );
         // if the robot is in (4, 9), it cannot move right because of the boxes
         assert_eq!(
-            can_move(&Complex::new(4, 9), &Direction::Right, &grid),
+            boxes_to_move(&Complex::new(4, 9), &Direction::Right, &grid),
             None
         );
     }
 
     #[test]
     fn test_update_grid() {
-        let (robot_pos, mut grid, directions) = parse_input(INPUT);
+        let (robot_pos, mut grid, directions) = parse_input(INPUT).unwrap();
         let direction = directions[0];
-        let new_pos = can_move(&robot_pos, &direction, &grid).unwrap();
-        update_grid(&robot_pos, &new_pos, &direction, &mut grid);
+        let boxes = boxes_to_move(&robot_pos, &direction, &grid).unwrap();
+        update_grid(&boxes, &direction, &mut grid);
         assert_eq!(grid[&(robot_pos + direction.val())], Element::Empty);
         assert_eq!(grid[&(robot_pos + 2 * direction.val())], Element::Box);
     }
 
     #[test]
     fn test_part1() {
-        let input = parse_input(INPUT);
+        let input = parse_input(INPUT).unwrap();
         assert_eq!(part1(input), 10092);
     }
 
     #[test]
     fn test_parse_input_p2() {
-        let (robot_pos, grid, directions) = parse_input_p2(INPUT);
+        let (robot_pos, grid, directions) = parse_input_p2(INPUT).unwrap();
         // It should be this grid
         //   00000000001111111111
         //   01234567890123456789
@@ -376,7 +460,7 @@ mod tests {
 
     #[test]
     fn test_can_move_p2() {
-        let (robot_pos, grid, _) = parse_input_p2(INPUT);
+        let (robot_pos, grid, _) = parse_input_p2(INPUT).unwrap();
         let direction = Direction::Up;
         let can_move_up = boxes_to_move(&robot_pos, &direction, &grid);
         assert_eq!(can_move_up, Some(HashSet::default()));
@@ -389,7 +473,7 @@ mod tests {
 
     #[test]
     fn test_can_move_p2_up_with_box_left() {
-        let (_, grid, _) = parse_input_p2(INPUT);
+        let (_, grid, _) = parse_input_p2(INPUT).unwrap();
         let direction = Direction::Up;
         let pos = Complex::new(6, 5);
         assert_eq!(grid[&(pos + direction.val())], ElementLarge::BoxLeft);
@@ -402,7 +486,7 @@ mod tests {
 
     #[test]
     fn test_can_move_p2_up_with_box_right() {
-        let (_, grid, _) = parse_input_p2(INPUT);
+        let (_, grid, _) = parse_input_p2(INPUT).unwrap();
         let direction = Direction::Up;
         let pos = Complex::new(7, 5);
         assert_eq!(grid[&(pos + direction.val())], ElementLarge::BoxRight);
@@ -415,7 +499,7 @@ mod tests {
 
     #[test]
     fn test_update_grid_p2() {
-        let (_, mut grid, _) = parse_input_p2(INPUT);
+        let (_, mut grid, _) = parse_input_p2(INPUT).unwrap();
         let direction = Direction::Up;
         let pos = Complex::new(7, 5);
 
@@ -433,7 +517,7 @@ mod tests {
         // 9 ####################
         let can_move_up = boxes_to_move(&pos, &direction, &grid).unwrap();
 
-        update_grid_p2(
+        update_grid(
             //&pos,
             &can_move_up,
             &direction,
@@ -462,7 +546,7 @@ mod tests {
 
     #[test]
     fn test_update_grid_p2_down_box_left() {
-        let (_, mut grid, _) = parse_input_p2(INPUT);
+        let (_, mut grid, _) = parse_input_p2(INPUT).unwrap();
         let direction = Direction::Down;
         let pos = Complex::new(14, 5);
 
@@ -480,7 +564,7 @@ mod tests {
         // 9 ####################
         let can_move_down = boxes_to_move(&pos, &direction, &grid).unwrap();
 
-        update_grid_p2(
+        update_grid(
             //&pos,
             &can_move_down,
             &direction,
@@ -506,7 +590,7 @@ mod tests {
         assert_eq!(grid[&Complex::new(14, 7)], ElementLarge::BoxLeft);
         assert_eq!(grid[&Complex::new(15, 8)], ElementLarge::BoxRight);
         assert_eq!(grid[&Complex::new(14, 8)], ElementLarge::BoxLeft);
-        _print_grid(&grid, &(pos + direction.val()));
+        println!("{}", render(&grid, pos + direction.val()));
     }
 
     #[test]
@@ -520,12 +604,53 @@ mod tests {
         // #######
 
         // <vv<<^^<<^^>v";
-        let (robot_pos, grid, directions) = parse_input_p2(INPUT);
-        _print_grid(&grid, &robot_pos);
+        let (robot_pos, grid, directions) = parse_input_p2(INPUT).unwrap();
+        println!("{}", render(&grid, robot_pos));
         assert_eq!(part2((robot_pos, grid, directions)), 9021);
         // panic!();
     }
 
+    #[test]
+    fn test_blocks_are_the_puzzle_elements() {
+        let (robot_pos, grid, directions) = parse_input(INPUT).unwrap();
+        let (robot_pos, grid) = scale::<Block<1, 1>>(robot_pos, &grid);
+        assert_eq!(simulate(robot_pos, grid, directions), 10092);
+
+        let (robot_pos, grid, directions) = parse_input(INPUT).unwrap();
+        let (robot_pos, grid) = scale::<Block<2, 1>>(robot_pos, &grid);
+        assert_eq!(simulate(robot_pos, grid, directions), 9021);
+    }
+
+    #[test]
+    fn test_three_wide_boxes() {
+        let (robot_pos, grid, directions) = parse_input(indoc! {
+            "######
+            #....#
+            #.O..#
+            #.O@.#
+            #....#
+            ######
+
+            <v<<^"
+        })
+        .unwrap();
+        let (robot_pos, grid) = scale::<Block<3, 1>>(robot_pos, &grid);
+        assert_eq!(robot_pos, Complex::new(9, 3));
+        let last = Replay::new(robot_pos, grid, directions).last().unwrap();
+        // the lower box only pushes the upper one with two of its tiles
+        assert_eq!(
+            last.to_text(),
+            indoc! {"
+                ##################
+                ###...[=]......###
+                ###..[=].......###
+                ###...@........###
+                ###............###
+                ##################
+            "}
+        );
+    }
+
     #[test]
     fn test_p2_complex_move() {
         //   012345678
@@ -535,7 +660,7 @@ mod tests {
         // 3 ##.[][][]
         // 4 ##..@..[]
         let robot_pos = Complex::new(4, 4);
-        let mut grid: Grid2 = HashMap::from_iter([
+        let mut grid = Grid2::from_iter([
             (Complex::new(0, 0), ElementLarge::Wall),
             (Complex::new(0, 1), ElementLarge::Wall),
             (Complex::new(0, 2), ElementLarge::Wall),
@@ -582,17 +707,17 @@ mod tests {
             (Complex::new(8, 3), ElementLarge::BoxRight),
             (Complex::new(8, 4), ElementLarge::BoxRight),
         ]);
-        _print_grid(&grid, &robot_pos);
+        println!("{}", render(&grid, robot_pos));
         let direction = Direction::Up;
         let final_positions = boxes_to_move(&robot_pos, &direction, &grid).unwrap();
         println!("{:#?}", final_positions);
-        update_grid_p2(
+        update_grid(
             //&robot_pos,
             &final_positions,
             &direction,
             &mut grid,
         );
-        _print_grid(&grid, &(robot_pos + direction.val()));
+        println!("{}", render(&grid, robot_pos + direction.val()));
         // panic!();
     }
 }
@@ -0,0 +1,184 @@
+use std::collections::HashSet;
+
+use super::{boxes_to_move, update_grid, Direction, Footprint};
+use crate::grid::{DenseGrid, Pos};
+
+/// A move as it was applied, enough to undo and redo it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delta {
+    pub direction: Direction,
+    /// Where the robot was before the move
+    pub robot: Pos,
+    /// The top left tiles of the pushed boxes, before the move
+    pub boxes: HashSet<Pos>,
+    pub blocked: bool,
+}
+
+/// A warehouse whose moves can be undone and redone
+#[derive(Debug, Clone)]
+pub struct Simulation<T> {
+    pub robot: Pos,
+    pub grid: DenseGrid<T>,
+    done: Vec<Delta>,
+    undone: Vec<Delta>,
+}
+
+impl<T: Footprint> Simulation<T> {
+    pub fn new(robot: Pos, grid: DenseGrid<T>) -> Self {
+        Simulation {
+            robot,
+            grid,
+            done: vec![],
+            undone: vec![],
+        }
+    }
+
+    /// The moves applied so far, the last one first to be undone
+    pub fn history(&self) -> &[Delta] {
+        &self.done
+    }
+
+    /// Whether a wall stops the robot from moving towards `direction`
+    pub fn is_blocked(&self, direction: Direction) -> bool {
+        boxes_to_move(&self.robot, &direction, &self.grid).is_none()
+    }
+
+    /// Move the robot, which forgets the moves that were undone. Returns whether the move was
+    /// blocked, it is recorded either way so that the history matches the instructions.
+    pub fn apply(&mut self, direction: Direction) -> bool {
+        let boxes = boxes_to_move(&self.robot, &direction, &self.grid);
+        let delta = Delta {
+            direction,
+            robot: self.robot,
+            blocked: boxes.is_none(),
+            boxes: boxes.unwrap_or_default(),
+        };
+        self.undone.clear();
+        self.redo_delta(&delta);
+        self.done.push(delta);
+        self.done.last().unwrap().blocked
+    }
+
+    /// Undo the last move, returning whether it was blocked, `None` if there is nothing to undo
+    pub fn undo(&mut self) -> Option<bool> {
+        let delta = self.done.pop()?;
+        if !delta.blocked {
+            let back = opposite(delta.direction);
+            let moved = delta
+                .boxes
+                .iter()
+                .map(|origin| origin + delta.direction.val())
+                .collect();
+            update_grid(&moved, &back, &mut self.grid);
+        }
+        self.robot = delta.robot;
+        let blocked = delta.blocked;
+        self.undone.push(delta);
+        Some(blocked)
+    }
+
+    /// Apply again the last undone move, returning whether it was blocked, `None` if there is
+    /// nothing to redo
+    pub fn redo(&mut self) -> Option<bool> {
+        let delta = self.undone.pop()?;
+        self.redo_delta(&delta);
+        let blocked = delta.blocked;
+        self.done.push(delta);
+        Some(blocked)
+    }
+
+    fn redo_delta(&mut self, delta: &Delta) {
+        if !delta.blocked {
+            update_grid(&delta.boxes, &delta.direction, &mut self.grid);
+            self.robot = delta.robot + delta.direction.val();
+        }
+    }
+}
+
+fn opposite(direction: Direction) -> Direction {
+    match direction {
+        Direction::Left => Direction::Right,
+        Direction::Right => Direction::Left,
+        Direction::Up => Direction::Down,
+        Direction::Down => Direction::Up,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day15::{parse_input_p2, render, Replay};
+    use indoc::indoc;
+    use num::Complex;
+    use pretty_assertions::assert_eq;
+
+    const INPUT: &str = indoc! {
+        "#######
+        #...#.#
+        #.....#
+        #..OO@#
+        #..O..#
+        #.....#
+        #######
+
+        <vv<<^^<<^^"
+    };
+
+    #[test]
+    fn test_apply() {
+        let (robot, grid, directions) = parse_input_p2(INPUT).unwrap();
+        let mut simulation = Simulation::new(robot, grid.clone());
+        let blocked: Vec<bool> = directions.iter().map(|d| simulation.apply(*d)).collect();
+        let frames: Vec<_> = Replay::new(robot, grid, directions).collect();
+        assert_eq!(
+            blocked,
+            frames.iter().map(|frame| frame.blocked).collect::<Vec<_>>()
+        );
+        let last = frames.last().unwrap();
+        assert_eq!(
+            (simulation.robot, &simulation.grid),
+            (last.robot, &last.grid)
+        );
+        assert_eq!(simulation.history().len(), 11);
+        assert_eq!(
+            simulation.history()[0].boxes,
+            HashSet::from([Complex::new(8, 3), Complex::new(6, 3)])
+        );
+        assert!(simulation.is_blocked(Direction::Up));
+        assert!(!simulation.is_blocked(Direction::Left));
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let (robot, grid, directions) = parse_input_p2(INPUT).unwrap();
+        let mut simulation = Simulation::new(robot, grid.clone());
+        assert_eq!(simulation.undo(), None);
+        let states: Vec<_> = directions
+            .iter()
+            .map(|d| {
+                simulation.apply(*d);
+                render(&simulation.grid, simulation.robot)
+            })
+            .collect();
+
+        for (i, state) in states.iter().enumerate().rev() {
+            assert_eq!(&render(&simulation.grid, simulation.robot), state);
+            assert!(simulation.undo().is_some(), "undo move {i}");
+        }
+        assert_eq!((simulation.robot, &simulation.grid), (robot, &grid));
+        assert_eq!(simulation.undo(), None);
+
+        for state in &states {
+            assert!(simulation.redo().is_some());
+            assert_eq!(&render(&simulation.grid, simulation.robot), state);
+        }
+        assert_eq!(simulation.redo(), None);
+
+        // a new move forgets the undone ones
+        assert_eq!(simulation.undo(), Some(false));
+        assert_eq!(simulation.undo(), Some(false));
+        assert!(!simulation.apply(Direction::Down));
+        assert_eq!(simulation.redo(), None);
+        assert_eq!(simulation.history().len(), 10);
+    }
+}
@@ -0,0 +1,345 @@
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+use super::{Block, Direction, Directions, Element, ElementLarge, Warehouse};
+use crate::grid::{DenseGrid, Pos};
+
+const ROBOT_COLOR: [u8; 3] = [220, 50, 47];
+
+/// A cell of a warehouse, drawn as a character in text frames and as a color in images
+pub trait Tile: Display {
+    fn color(&self) -> [u8; 3];
+}
+
+impl Tile for Element {
+    fn color(&self) -> [u8; 3] {
+        match self {
+            Element::Empty => [32, 32, 32],
+            Element::Wall => [128, 128, 128],
+            Element::Box => [181, 137, 0],
+        }
+    }
+}
+
+impl Tile for ElementLarge {
+    fn color(&self) -> [u8; 3] {
+        match self {
+            ElementLarge::Empty => [32, 32, 32],
+            ElementLarge::Wall => [128, 128, 128],
+            // a darker right side shows where one wide box ends and the next one starts
+            ElementLarge::BoxLeft => [181, 137, 0],
+            ElementLarge::BoxRight => [140, 106, 0],
+        }
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> Tile for Block<WIDTH, HEIGHT> {
+    fn color(&self) -> [u8; 3] {
+        match self {
+            Block::Empty => [32, 32, 32],
+            Block::Wall => [128, 128, 128],
+            Block::Box { column: 0, .. } => [181, 137, 0],
+            Block::Box { .. } => [140, 106, 0],
+        }
+    }
+}
+
+/// The map as text, with the robot as `@`
+pub fn render<T: Display>(grid: &DenseGrid<T>, robot: Pos) -> String {
+    let grid: DenseGrid<String> = grid
+        .iter()
+        .map(|(pos, e)| {
+            let cell = if pos == robot {
+                String::from("@")
+            } else {
+                e.to_string()
+            };
+            (pos, cell)
+        })
+        .collect();
+    grid.to_string()
+}
+
+/// The warehouse after the move number `index`, starting at 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame<T> {
+    pub index: usize,
+    pub direction: Direction,
+    pub blocked: bool,
+    pub robot: Pos,
+    pub grid: DenseGrid<T>,
+}
+
+impl<T: Tile> Frame<T> {
+    pub fn to_text(&self) -> String {
+        render(&self.grid, self.robot)
+    }
+
+    /// The RGB pixels of the frame row by row, with `scale` x `scale` pixels per cell
+    fn pixels(&self, scale: usize) -> Vec<u8> {
+        let mut image = vec![];
+        for row in self.grid.rows() {
+            let pixels: Vec<u8> = row
+                .flat_map(|(pos, e)| {
+                    let color = if pos == self.robot {
+                        ROBOT_COLOR
+                    } else {
+                        e.color()
+                    };
+                    color.repeat(scale)
+                })
+                .collect();
+            for _ in 0..scale {
+                image.extend(&pixels);
+            }
+        }
+        image
+    }
+
+    /// A binary PPM image, with `scale` x `scale` pixels per cell
+    pub fn to_ppm(&self, scale: usize) -> Vec<u8> {
+        let width = self.grid.width() as usize * scale;
+        let height = self.grid.height() as usize * scale;
+        let mut image = format!("P6\n{width} {height}\n255\n").into_bytes();
+        image.extend(self.pixels(scale));
+        image
+    }
+
+    /// A PNG image, with `scale` x `scale` pixels per cell
+    pub fn to_png(&self, scale: usize) -> Result<Vec<u8>> {
+        let width = self.grid.width() as u32 * scale as u32;
+        let height = self.grid.height() as u32 * scale as u32;
+        let mut image = vec![];
+        let mut encoder = png::Encoder::new(&mut image, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels(scale))?;
+        writer.finish()?;
+        Ok(image)
+    }
+}
+
+/// The moves applied one at a time, yielding a [`Frame`] after each of them
+#[derive(Debug, Clone)]
+pub struct Replay<T> {
+    robot: Pos,
+    grid: DenseGrid<T>,
+    directions: std::vec::IntoIter<Direction>,
+    index: usize,
+}
+
+impl<T> Replay<T> {
+    pub fn new(robot: Pos, grid: DenseGrid<T>, directions: Directions) -> Self {
+        Replay {
+            robot,
+            grid,
+            directions: directions.into_iter(),
+            index: 0,
+        }
+    }
+}
+
+impl<T: Clone> Iterator for Replay<T>
+where
+    DenseGrid<T>: Warehouse,
+{
+    type Item = Frame<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let direction = self.directions.next()?;
+        self.index += 1;
+        let moved = self.grid.push(self.robot, direction);
+        if let Some(robot) = moved {
+            self.robot = robot;
+        }
+        Some(Frame {
+            index: self.index,
+            direction,
+            blocked: moved.is_none(),
+            robot: self.robot,
+            grid: self.grid.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameFormat {
+    Text,
+    /// With `scale` x `scale` pixels per cell
+    Ppm {
+        scale: usize,
+    },
+    /// With `scale` x `scale` pixels per cell
+    Png {
+        scale: usize,
+    },
+}
+
+/// Write each frame to `dir`, as `frame_00001.txt`, `frame_00001.ppm` or `frame_00001.png`, and
+/// return the number of frames written
+pub fn export_frames<T: Tile>(
+    frames: impl IntoIterator<Item = Frame<T>>,
+    dir: &Path,
+    format: FrameFormat,
+) -> Result<usize> {
+    if matches!(
+        format,
+        FrameFormat::Ppm { scale: 0 } | FrameFormat::Png { scale: 0 }
+    ) {
+        bail!("the scale of the images must be at least 1");
+    }
+    fs::create_dir_all(dir)?;
+    let mut count = 0;
+    for frame in frames {
+        let name = format!("frame_{:05}", frame.index);
+        match format {
+            FrameFormat::Text => fs::write(dir.join(name + ".txt"), frame.to_text())?,
+            FrameFormat::Ppm { scale } => fs::write(dir.join(name + ".ppm"), frame.to_ppm(scale))?,
+            FrameFormat::Png { scale } => fs::write(dir.join(name + ".png"), frame.to_png(scale)?)?,
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day15::{parse_input, parse_input_p2};
+    use indoc::indoc;
+    use num::Complex;
+    use pretty_assertions::assert_eq;
+
+    const INPUT: &str = indoc! {
+        "########
+        #..O.O.#
+        ##@.O..#
+        #...O..#
+        #.#.O..#
+        #...O..#
+        #......#
+        ########
+
+        <^^>>>vv<v>>v<<"
+    };
+
+    #[test]
+    fn test_replay() {
+        let (robot, grid, directions) = parse_input(INPUT).unwrap();
+        let frames: Vec<_> = Replay::new(robot, grid, directions).collect();
+        assert_eq!(frames.len(), 15);
+        assert_eq!(
+            frames
+                .iter()
+                .filter(|frame| frame.blocked)
+                .map(|frame| frame.index)
+                .collect::<Vec<_>>(),
+            vec![1, 3, 6, 8, 15]
+        );
+        assert_eq!(frames[1].robot, Complex::new(2, 1));
+        assert_eq!(
+            frames[14].to_text(),
+            indoc! {"
+                ########
+                #....OO#
+                ##.....#
+                #.....O#
+                #.#O@..#
+                #...O..#
+                #...O..#
+                ########
+            "}
+        );
+    }
+
+    #[test]
+    fn test_replay_p2() {
+        let (robot, grid, directions) = parse_input_p2(INPUT).unwrap();
+        let last = Replay::new(robot, grid, directions).last().unwrap();
+        assert_eq!(last.index, 15);
+        assert_eq!(
+            last.to_text(),
+            indoc! {"
+                ################
+                ##......[][]..##
+                ####....[]....##
+                ##......[]....##
+                ##..##...[]...##
+                ##....@.......##
+                ##......[]....##
+                ################
+            "}
+        );
+    }
+
+    #[test]
+    fn test_to_ppm() {
+        let (robot, grid, _) = parse_input("####\n#@O#\n####\n\n>").unwrap();
+        let frame = Replay::new(robot, grid, vec![Direction::Right])
+            .next()
+            .unwrap();
+        assert!(frame.blocked);
+        let image = frame.to_ppm(2);
+        let header = "P6\n8 6\n255\n";
+        assert_eq!(&image[..header.len()], header.as_bytes());
+        assert_eq!(image.len(), header.len() + 8 * 6 * 3);
+        let pixel = |x: usize, y: usize| {
+            let start = header.len() + (y * 8 + x) * 3;
+            [image[start], image[start + 1], image[start + 2]]
+        };
+        assert_eq!(pixel(2, 2), ROBOT_COLOR);
+        assert_eq!(pixel(3, 3), ROBOT_COLOR);
+        assert_eq!(pixel(5, 2), Element::Box.color());
+        assert_eq!(pixel(1, 1), Element::Wall.color());
+    }
+
+    #[test]
+    fn test_to_png() {
+        let (robot, grid, _) = parse_input("####\n#@O#\n####\n\n>").unwrap();
+        let frame = Replay::new(robot, grid, vec![Direction::Right])
+            .next()
+            .unwrap();
+        let image = frame.to_png(2).unwrap();
+        let mut reader = png::Decoder::new(std::io::Cursor::new(image))
+            .read_info()
+            .unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (8, 6));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        // the same pixels as the PPM image, after its header
+        let ppm = frame.to_ppm(2);
+        assert_eq!(&pixels[..info.buffer_size()], &ppm[ppm.len() - 8 * 6 * 3..]);
+    }
+
+    #[test]
+    fn test_export_frames() {
+        let (robot, grid, directions) = parse_input(INPUT).unwrap();
+        let dir = std::env::temp_dir().join(format!("day15_frames_{}", std::process::id()));
+        let frames = Replay::new(robot, grid, directions);
+        assert_eq!(
+            export_frames(frames.clone(), &dir, FrameFormat::Text).unwrap(),
+            15
+        );
+        assert_eq!(
+            export_frames(frames.take(2), &dir, FrameFormat::Ppm { scale: 4 }).unwrap(),
+            2
+        );
+        let text = fs::read_to_string(dir.join("frame_00015.txt")).unwrap();
+        assert!(text.contains("#.#O@..#"));
+        assert!(dir.join("frame_00002.ppm").exists());
+        assert!(!dir.join("frame_00003.ppm").exists());
+        let (robot, grid, directions) = parse_input(INPUT).unwrap();
+        let frames = Replay::new(robot, grid, directions);
+        assert_eq!(
+            export_frames(frames.take(1), &dir, FrameFormat::Png { scale: 1 }).unwrap(),
+            1
+        );
+        assert!(dir.join("frame_00001.png").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,261 @@
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+
+use itertools::Itertools;
+use pathfinding::prelude::bfs;
+
+use super::{boxes_to_move_with, Direction, Directions, Footprint};
+use crate::grid::{DenseGrid, Pos};
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+/// The number of states a search may explore
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Budget {
+    pub states: usize,
+    exhausted: bool,
+}
+
+impl Budget {
+    pub fn new(states: usize) -> Self {
+        Budget {
+            states,
+            exhausted: false,
+        }
+    }
+
+    /// Whether the last search ran out of states before finding a solution or proving there
+    /// is none, which tells apart its two reasons for returning `None`
+    pub fn exhausted(&self) -> bool {
+        self.exhausted
+    }
+}
+
+/// The robot, and the top left tiles of the boxes in a canonical order
+type State = (Pos, Vec<Pos>);
+
+fn box_origins<T: Footprint>(grid: &DenseGrid<T>) -> Vec<Pos> {
+    grid.iter()
+        .filter(|(_, e)| e.box_offset() == Some(Pos::new(0, 0)))
+        .map(|(pos, _)| pos)
+        .sorted_by_key(|pos| (pos.im, pos.re))
+        .collect()
+}
+
+/// The tiles covered by the boxes whose top left tiles are `boxes`
+fn box_tiles<T: Footprint>(boxes: &[Pos]) -> HashMap<Pos, T> {
+    let tiles = T::box_tiles();
+    boxes
+        .iter()
+        .flat_map(|origin| {
+            tiles
+                .iter()
+                .map(move |(offset, tile)| (origin + offset, *tile))
+        })
+        .collect()
+}
+
+/// The top left tiles from which a box alone in `walls` can still be pushed onto one of
+/// `target`. A box anywhere else is stuck, whatever the other boxes do.
+fn live_positions<T: Footprint>(walls: &DenseGrid<T>, target: &HashSet<Pos>) -> HashSet<Pos> {
+    let is_free = |pos: Pos| walls.get(&pos).is_some_and(|e| *e != T::WALL);
+    let tiles: Vec<Pos> = T::box_tiles().into_iter().map(|(o, _)| o).collect();
+    let fits = |origin: Pos| tiles.iter().all(|offset| is_free(origin + offset));
+    let mut live: HashSet<Pos> = target.iter().copied().filter(|p| fits(*p)).collect();
+    let mut to_visit: Vec<Pos> = live.iter().copied().collect();
+    while let Some(pos) = to_visit.pop() {
+        for direction in DIRECTIONS {
+            // the box came from `previous`, pushed by the robot standing right behind it
+            let previous = pos - direction.val();
+            let pushable = tiles.iter().any(|offset| {
+                let behind = offset - direction.val();
+                !tiles.contains(&behind) && is_free(previous + behind)
+            });
+            if pushable && fits(previous) && live.insert(previous) {
+                to_visit.push(previous);
+            }
+        }
+    }
+    live
+}
+
+/// Find a shortest sequence of moves after which the top left tiles of the boxes are exactly
+/// `target`, wherever the robot ends. Returns `None` when the layout is unreachable, or when
+/// more than `budget.states` states would have to be explored to know, which is then flagged
+/// by [`Budget::exhausted`].
+pub fn solve<T: Footprint>(
+    robot: Pos,
+    grid: &DenseGrid<T>,
+    target: &HashSet<Pos>,
+    budget: &mut Budget,
+) -> Option<Directions> {
+    budget.exhausted = false;
+    let boxes = box_origins(grid);
+    let walls: DenseGrid<T> = grid
+        .iter()
+        .map(|(pos, e)| {
+            (
+                pos,
+                if e.box_offset().is_some() {
+                    T::EMPTY
+                } else {
+                    *e
+                },
+            )
+        })
+        .collect();
+    let live = live_positions(&walls, target);
+    if boxes.len() != target.len() || boxes.iter().any(|origin| !live.contains(origin)) {
+        return None;
+    }
+
+    let explored = Cell::new(0);
+    let exhausted = Cell::new(false);
+    let successors = |(robot, boxes): &State| -> Vec<State> {
+        if explored.get() == budget.states {
+            exhausted.set(true);
+            return vec![];
+        }
+        explored.set(explored.get() + 1);
+        let tiles = box_tiles(boxes);
+        // past the edges of a map without walls around it, as if there were some
+        let tile_at = |pos: &Pos| {
+            tiles
+                .get(pos)
+                .or(walls.get(pos))
+                .copied()
+                .unwrap_or(T::WALL)
+        };
+        DIRECTIONS
+            .iter()
+            .filter_map(|direction| {
+                let moved = boxes_to_move_with(robot, direction, tile_at)?;
+                let step = direction.val();
+                // pushing a box where it can no longer reach a target is a dead end
+                if moved.iter().any(|origin| !live.contains(&(origin + step))) {
+                    return None;
+                }
+                let boxes = boxes
+                    .iter()
+                    .map(|origin| match moved.contains(origin) {
+                        true => origin + step,
+                        false => *origin,
+                    })
+                    .sorted_by_key(|pos| (pos.im, pos.re))
+                    .collect();
+                Some((robot + step, boxes))
+            })
+            .collect()
+    };
+    let solved = |(_, boxes): &State| boxes.iter().all(|origin| target.contains(origin));
+    let path = bfs(&(robot, boxes), successors, solved);
+    budget.exhausted = path.is_none() && exhausted.get();
+
+    path.map(|path| {
+        path.iter()
+            .tuple_windows()
+            .map(|((from, _), (to, _))| {
+                *DIRECTIONS
+                    .iter()
+                    .find(|direction| from + direction.val() == *to)
+                    .unwrap()
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day15::{parse_input, parse_input_p2, Simulation};
+    use num::Complex;
+    use pretty_assertions::assert_eq;
+
+    /// The top left tiles of the boxes after the robot applied `directions`
+    fn play<T: Footprint>(robot: Pos, grid: DenseGrid<T>, directions: &Directions) -> Vec<Pos> {
+        let mut simulation = Simulation::new(robot, grid);
+        for direction in directions {
+            assert!(!simulation.apply(*direction), "{directions:?}");
+        }
+        box_origins(&simulation.grid)
+    }
+
+    #[test]
+    fn test_solve() {
+        let (robot, grid, _) = parse_input("#####\n#@O.#\n#####\n\n<").unwrap();
+        let target = HashSet::from([Complex::new(3, 1)]);
+        let mut budget = Budget::new(100);
+        assert_eq!(
+            solve(robot, &grid, &target, &mut budget),
+            Some(vec![Direction::Right])
+        );
+        let target = HashSet::from([Complex::new(2, 1)]);
+        assert_eq!(solve(robot, &grid, &target, &mut budget), Some(vec![]));
+        assert!(!budget.exhausted());
+    }
+
+    #[test]
+    fn test_solve_open_map() {
+        let (robot, grid, _) = parse_input(".@O..\n.....\n\n<").unwrap();
+        let target = HashSet::from([Complex::new(4, 0)]);
+        let directions = solve(robot, &grid, &target, &mut Budget::new(100)).unwrap();
+        assert_eq!(play(robot, grid, &directions), vec![Complex::new(4, 0)]);
+    }
+
+    #[test]
+    fn test_solve_wide_boxes() {
+        let map = "########\n#......#\n#.O..O.#\n#..@...#\n#......#\n########\n\n<";
+        let (robot, grid, _) = parse_input_p2(map).unwrap();
+        let target = HashSet::from([Complex::new(4, 1), Complex::new(10, 4)]);
+        let directions = solve(robot, &grid, &target, &mut Budget::new(100_000)).unwrap();
+        let mut expected = target.into_iter().collect_vec();
+        expected.sort_by_key(|pos| (pos.im, pos.re));
+        assert_eq!(play(robot, grid, &directions), expected);
+        assert_eq!(directions.len(), 10);
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let map = "######\n#....#\n#.O..#\n#..@.#\n#....#\n######\n\n<";
+        let (robot, grid, _) = parse_input(map).unwrap();
+        // a box along a wall cannot be pushed away from it
+        assert_eq!(
+            live_positions(&grid, &HashSet::from([Complex::new(2, 2)])),
+            HashSet::from([
+                Complex::new(2, 2),
+                Complex::new(3, 2),
+                Complex::new(2, 3),
+                Complex::new(3, 3),
+            ])
+        );
+        let corner = HashSet::from([Complex::new(1, 1)]);
+        assert!(solve(robot, &grid, &corner, &mut Budget::new(100)).is_some());
+        let (robot, grid, _) = parse_input(&map.replace("#.O.", "#O..")).unwrap();
+        let target = HashSet::from([Complex::new(2, 2)]);
+        // caught by the dead positions, without exploring anything
+        let mut budget = Budget::new(0);
+        assert_eq!(solve(robot, &grid, &target, &mut budget), None);
+        assert!(!budget.exhausted());
+        let two_boxes = HashSet::from([Complex::new(2, 2), Complex::new(3, 3)]);
+        assert_eq!(solve(robot, &grid, &two_boxes, &mut budget), None);
+        assert!(!budget.exhausted());
+    }
+
+    #[test]
+    fn test_budget() {
+        let map = "########\n#......#\n#.O..O.#\n#..@...#\n#......#\n########\n\n<";
+        let (robot, grid, _) = parse_input_p2(map).unwrap();
+        let target = HashSet::from([Complex::new(4, 1), Complex::new(10, 4)]);
+        let mut budget = Budget::new(10);
+        assert_eq!(solve(robot, &grid, &target, &mut budget), None);
+        assert!(budget.exhausted());
+        budget.states = 100_000;
+        assert!(solve(robot, &grid, &target, &mut budget).is_some());
+        assert!(!budget.exhausted());
+    }
+}
@@ -1,71 +1,160 @@
+use anyhow::{bail, Result};
 use num::Complex;
-use rustc_hash::FxHashMap as HashMap;
-use rustc_hash::FxHashSet as HashSet;
 
-use pathfinding::prelude::{astar, astar_bag};
+use pathfinding::prelude::astar;
+
+use crate::grid::{self, Pos};
+use crate::params::{self, parse_param, unknown_param};
+use crate::parsers::ParseError;
+
+mod alternatives;
+mod report;
+
+pub use alternatives::{k_shortest_paths, near_optimal_tiles};
+pub use report::{BestPath, MazeReport};
 
-type Pos = Complex<isize>;
 type Dir = Complex<isize>;
-type Grid = HashMap<Pos, Element>;
+type Grid = grid::DenseGrid<Element>;
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum Element {
     Wall,
     Empty,
+    /// A digit, the cost of entering the tile when the tiles are weighted
+    Weighted(u8),
 }
 
-pub fn parse_input(input: &str) -> (Pos, Pos, Grid) {
-    let mut grid: Grid = HashMap::default();
-    let mut start = Complex::i();
-    let mut target = Complex::i();
-    for (j, line) in input.lines().enumerate() {
-        for (i, c) in line.chars().enumerate() {
-            let pos = Complex::new(i as isize, j as isize);
-            let e = match c {
-                'S' => {
-                    start = pos;
-                    Element::Empty
-                }
-                'E' => {
-                    target = pos;
-                    Element::Empty
+/// The cost model of the moves of the reindeer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    /// The cost of moving to the next tile
+    pub forward: isize,
+    /// The cost of a quarter turn, paid before moving
+    pub turn: isize,
+    /// Whether the reindeer can turn around in one move, for two turns
+    pub u_turn: bool,
+    pub heading: Dir,
+    /// Whether entering a digit tile costs its value rather than `forward`
+    pub tile_weights: bool,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            forward: 1,
+            turn: 1000,
+            u_turn: false,
+            heading: Complex::new(1, 0),
+            tile_weights: false,
+        }
+    }
+}
+
+impl params::Params for Params {
+    const DAY: u8 = 16;
+
+    fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        match name {
+            "forward" => self.forward = parse_param(name, value)?,
+            "turn" => self.turn = parse_param(name, value)?,
+            "u_turn" => self.u_turn = parse_param(name, value)?,
+            "tile_weights" => self.tile_weights = parse_param(name, value)?,
+            "heading" => {
+                self.heading = match value {
+                    "east" => Complex::new(1, 0),
+                    "south" => Complex::new(0, 1),
+                    "west" => Complex::new(-1, 0),
+                    "north" => Complex::new(0, -1),
+                    _ => bail!(
+                        "invalid value {value:?} for heading, expected east, south, west or north"
+                    ),
                 }
-                '.' => Element::Empty,
-                '#' => Element::Wall,
-                _ => panic!("Unknown character {c}"),
-            };
-            grid.insert(pos, e);
+            }
+            _ => unknown_param(name)?,
         }
+        if self.forward < 0 || self.turn < 0 {
+            bail!("the costs cannot be negative");
+        }
+        Ok(())
     }
-    (start, target, grid)
 }
 
-fn successors(pos: &Pos, dir: &Dir, grid: &Grid) -> Vec<((Pos, Dir), isize)> {
-    let mut next = vec![];
-    // One cost to keep going in the same direction
-    if let Some(e) = grid.get(&(pos + dir)) {
-        if *e == Element::Empty {
-            next.push(((*pos + *dir, *dir), 1));
+impl Params {
+    /// The cost of entering a tile, `None` for walls
+    fn enter(&self, e: Element) -> Option<isize> {
+        match e {
+            Element::Wall => None,
+            Element::Weighted(weight) if self.tile_weights => Some(weight as isize),
+            _ => Some(self.forward),
         }
     }
-    // Cost increased by 1000 to rotate once
-    for rotation in [Complex::i(), -Complex::i()] {
-        let new_dir = dir * rotation;
-        if let Some(e) = grid.get(&(pos + new_dir)) {
-            if *e == Element::Empty {
-                next.push(((*pos + new_dir, new_dir), 1001));
-            }
+
+    /// A lower bound of the cost of every move in `grid`, for the heuristic of the searches
+    fn min_step(&self, grid: &Grid) -> isize {
+        grid.iter()
+            .filter_map(|(_, e)| self.enter(*e))
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<(Pos, Pos, Grid)> {
+    let mut start = None;
+    let mut target = None;
+    let grid = Grid::try_parse(input, |pos, c| match c {
+        'S' => {
+            start = Some(pos);
+            Ok(Element::Empty)
         }
+        'E' => {
+            target = Some(pos);
+            Ok(Element::Empty)
+        }
+        '.' => Ok(Element::Empty),
+        '#' => Ok(Element::Wall),
+        c if c.is_ascii_digit() => Ok(Element::Weighted(c as u8 - b'0')),
+        c => Err(format!("unknown character {c:?}")),
+    })?;
+    let start = start.ok_or_else(|| ParseError::at_end(input, "no start 'S' in the maze"))?;
+    let target = target.ok_or_else(|| ParseError::at_end(input, "no end 'E' in the maze"))?;
+    Ok((start, target, grid))
+}
+
+/// The moves from `(pos, dir)`, none from `target`: a path ends there, even if moving on is free
+fn successors(
+    pos: &Pos,
+    dir: &Dir,
+    target: Pos,
+    grid: &Grid,
+    params: &Params,
+) -> Vec<((Pos, Dir), isize)> {
+    if *pos == target {
+        return vec![];
     }
-    next
+    let mut rotations = vec![
+        (Complex::new(1, 0), 0),
+        (Complex::i(), params.turn),
+        (-Complex::i(), params.turn),
+    ];
+    if params.u_turn {
+        rotations.push((Complex::new(-1, 0), 2 * params.turn));
+    }
+    rotations
+        .into_iter()
+        .filter_map(|(rotation, cost)| {
+            let new_dir = dir * rotation;
+            let enter = params.enter(*grid.get(&(pos + new_dir))?)?;
+            Some(((pos + new_dir, new_dir), cost + enter))
+        })
+        .collect()
 }
 
-pub fn part1((start, target, grid): (Pos, Pos, Grid)) -> isize {
-    let initial_dir = Complex::new(1, 0);
+pub fn part1(((start, target, grid), params): ((Pos, Pos, Grid), Params)) -> isize {
+    let min_step = params.min_step(&grid);
     if let Some((_, cost)) = astar(
-        &(start, initial_dir),
-        |(pos, dir)| successors(pos, dir, &grid),
-        |(pos, _)| (target - pos).l1_norm(),
+        &(start, params.heading),
+        |(pos, dir)| successors(pos, dir, target, &grid, &params),
+        |(pos, _)| (target - pos).l1_norm() * min_step,
         |(pos, _)| *pos == target,
     ) {
         cost
@@ -74,23 +163,14 @@ pub fn part1((start, target, grid): (Pos, Pos, Grid)) -> isize {
     }
 }
 
-pub fn part2((start, target, grid): (Pos, Pos, Grid)) -> usize {
-    let initial_dir = Complex::new(1, 0);
-    let mut best_pos: HashSet<Pos> = HashSet::default();
-    if let Some((solutions, _cost)) = astar_bag(
-        &(start, initial_dir),
-        |(pos, dir)| successors(pos, dir, &grid),
-        |(pos, _)| (target - pos).l1_norm(),
-        |(pos, _)| *pos == target,
-    ) {
-        for path in solutions {
-            let positions: HashSet<Pos> = path.iter().map(|(p, _)| *p).collect();
-            best_pos.extend(positions);
-        }
-        best_pos.len()
-    } else {
+/// Count the tiles whose cheapest cost from the start plus cheapest cost to the end is the best
+/// cost, with one search each way, as the best paths are far too many to list in open mazes
+pub fn part2(((start, target, grid), params): ((Pos, Pos, Grid), Params)) -> usize {
+    let tiles = near_optimal_tiles(start, target, &grid, &params, 0);
+    if tiles.is_empty() {
         panic!("No solution found");
     }
+    tiles.len()
 }
 
 #[cfg(test)]
@@ -99,27 +179,11 @@ mod tests {
     use indoc::indoc;
     use pretty_assertions::assert_eq;
 
-    const INPUT: &str = indoc! {
-        "###############
-        #.......#....E#
-        #.#.###.#.###.#
-        #.....#.#...#.#
-        #.###.#####.#.#
-        #.#.#.......#.#
-        #.#.#####.###.#
-        #...........#.#
-        ###.#.#####.#.#
-        #...#.....#.#.#
-        #.#.#.###.#.#.#
-        #.....#...#.#.#
-        #.###.#.#.#.#.#
-        #S..#.....#...#
-        ###############"
-    };
+    const INPUT: &str = include_str!("../fixtures/day16.txt");
 
     #[test]
     fn test_parse_input() {
-        let (start, target, grid) = parse_input(INPUT);
+        let (start, target, grid) = parse_input(INPUT).unwrap();
         assert_eq!(start, Complex::new(1, 13));
         assert_eq!(target, Complex::new(13, 1));
         assert_eq!(grid[&Complex::new(0, 0)], Element::Wall);
@@ -127,15 +191,169 @@ mod tests {
         assert_eq!(grid[&Complex::new(1, 13)], Element::Empty);
     }
 
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("####\n#S.#\n####").unwrap_err();
+        assert_eq!(e.to_string(), "line 3, column 5: no end 'E' in the maze");
+        let e = parse_input("####\n#SE#\n##o#").unwrap_err();
+        assert_eq!(e.to_string(), "line 3, column 3: unknown character 'o'");
+    }
+
     #[test]
     fn test_part1() {
-        let input = parse_input(INPUT);
-        assert_eq!(part1(input), 7036);
+        let input = parse_input(INPUT).unwrap();
+        assert_eq!(part1((input, Params::default())), 7036);
     }
 
     #[test]
     fn test_part2() {
-        let input = parse_input(INPUT);
-        assert_eq!(part2(input), 45);
+        let input = parse_input(INPUT).unwrap();
+        assert_eq!(part2((input, Params::default())), 45);
+    }
+
+    #[test]
+    fn test_part2_is_the_best_paths() {
+        let params = Params::default();
+        let (start, target, grid) = parse_input(INPUT).unwrap();
+        let models = [
+            params,
+            Params { turn: 1, ..params },
+            Params { turn: 0, ..params },
+            Params {
+                u_turn: true,
+                heading: Complex::new(-1, 0),
+                ..params
+            },
+            Params {
+                forward: 0,
+                ..params
+            },
+        ];
+        for params in models {
+            let report = MazeReport::new(start, target, &grid, &params).unwrap();
+            assert_eq!(
+                part2(((start, target, grid.clone()), params)),
+                report.tiles().len(),
+                "{params:?}"
+            );
+        }
+
+        // free moves in the room past the end, which the best paths must not wander into
+        let input = indoc! {
+            "#######
+            #S1E..#
+            ####..#
+            #######"
+        };
+        let (start, target, grid) = parse_input(input).unwrap();
+        let free = Params {
+            forward: 0,
+            turn: 0,
+            tile_weights: true,
+            ..params
+        };
+        let report = MazeReport::new(start, target, &grid, &free).unwrap();
+        assert_eq!(report.cost, 1);
+        assert_eq!(report.tiles().len(), 3);
+        assert_eq!(part2(((start, target, grid), free)), 3);
+    }
+
+    #[test]
+    fn test_part2_open_grid() {
+        let size = 60;
+        let row = |inner: &str| format!("#{inner}#\n");
+        let mut input = row(&"#".repeat(size));
+        input += &row(&(".".repeat(size - 1) + "E"));
+        for _ in 0..size - 2 {
+            input += &row(&".".repeat(size));
+        }
+        input += &row(&("S".to_string() + &".".repeat(size - 1)));
+        input += &row(&"#".repeat(size));
+        let params = Params::default();
+        let (start, target, grid) = parse_input(&input).unwrap();
+        // a single turn, at the bottom right corner
+        assert_eq!(part2(((start, target, grid.clone()), params)), 2 * size - 1);
+        // every path going only up and right is one of the best, too many to list
+        let free_turns = Params { turn: 0, ..params };
+        assert_eq!(part2(((start, target, grid), free_turns)), size * size);
+    }
+
+    #[test]
+    fn test_params() {
+        use params::Params as _;
+        let overrides = ["day16.turn=10", "day16.heading=north", "day16.u_turn=true"]
+            .map(|o| o.parse().unwrap());
+        let params = Params::with_overrides(&overrides).unwrap();
+        assert_eq!(
+            params,
+            Params {
+                turn: 10,
+                heading: Complex::new(0, -1),
+                u_turn: true,
+                ..Params::default()
+            }
+        );
+        let e = Params::with_overrides(&["day16.heading=up".parse().unwrap()]).unwrap_err();
+        assert_eq!(
+            format!("{e:#}"),
+            "invalid parameter day16.heading: invalid value \"up\" for heading, expected east, south, west or north"
+        );
+        assert!(Params::with_overrides(&["day16.turn=-1".parse().unwrap()]).is_err());
+    }
+
+    #[test]
+    fn test_cost_models() {
+        let params = Params::default();
+        let (start, target, grid) = parse_input(INPUT).unwrap();
+        let solve = |params| part1(((start, target, grid.clone()), params));
+        assert_eq!(solve(Params { turn: 1, ..params }), 38);
+        assert_eq!(
+            solve(Params {
+                heading: Complex::new(0, -1),
+                ..params
+            }),
+            6036
+        );
+        // in a corridor, a reindeer facing the wrong way needs a U-turn
+        let params = Params::default();
+        let (start, target, grid) = parse_input("######\n#E..S#\n######").unwrap();
+        assert_eq!(MazeReport::new(start, target, &grid, &params), None);
+        let west = Params {
+            heading: Complex::new(-1, 0),
+            ..params
+        };
+        assert_eq!(part1(((start, target, grid.clone()), west)), 3);
+        let u_turn = Params {
+            u_turn: true,
+            turn: 300,
+            ..params
+        };
+        assert_eq!(part1(((start, target, grid), u_turn)), 603);
+    }
+
+    #[test]
+    fn test_tile_weights() {
+        let input = indoc! {
+            "#######
+            #.....#
+            #.###.#
+            #S191E#
+            #######"
+        };
+        let params = Params::default();
+        let (start, target, grid) = parse_input(input).unwrap();
+        let weighted = Params {
+            turn: 0,
+            tile_weights: true,
+            ..params
+        };
+        // around the 9, rather than through it
+        assert_eq!(part1(((start, target, grid.clone()), weighted)), 8);
+        assert_eq!(part2(((start, target, grid.clone()), weighted)), 9);
+        assert_eq!(
+            part1(((start, target, grid.clone()), Params { turn: 0, ..params })),
+            4
+        );
+        assert_eq!(part1(((start, target, grid), params)), 4);
     }
 }
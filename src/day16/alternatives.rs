@@ -0,0 +1,169 @@
+use num::Complex;
+use pathfinding::prelude::{dijkstra_reach, yen};
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+
+use super::{successors, BestPath, Dir, Element, Grid, Params};
+use crate::grid::Pos;
+
+type State = (Pos, Dir);
+
+/// The states from which one move leads to `(pos, dir)`, with the cost of that move
+fn predecessors(pos: &Pos, dir: &Dir, grid: &Grid, params: &Params) -> Vec<(State, isize)> {
+    let Some(enter) = grid.get(pos).and_then(|e| params.enter(*e)) else {
+        return vec![];
+    };
+    let previous = pos - dir;
+    if grid.get(&previous).is_none_or(|e| *e == Element::Wall) {
+        return vec![];
+    }
+    let mut rotations = vec![
+        (Complex::new(1, 0), 0),
+        (Complex::i(), params.turn),
+        (-Complex::i(), params.turn),
+    ];
+    if params.u_turn {
+        rotations.push((Complex::new(-1, 0), 2 * params.turn));
+    }
+    rotations
+        .into_iter()
+        .map(|(rotation, cost)| ((previous, dir * rotation.conj()), cost + enter))
+        .collect()
+}
+
+/// The cost of the cheapest path from the start to each reachable state, stopping at the target
+pub(super) fn costs_from_start(
+    start: Pos,
+    target: Pos,
+    grid: &Grid,
+    params: &Params,
+) -> HashMap<State, isize> {
+    dijkstra_reach(&(start, params.heading), |(pos, dir)| {
+        successors(pos, dir, target, grid, params)
+    })
+    .map(|item| (item.node, item.total_cost))
+    .collect()
+}
+
+/// The cost of the cheapest path from each state to the target, whatever the final heading
+pub(super) fn costs_to_target(target: Pos, grid: &Grid, params: &Params) -> HashMap<State, isize> {
+    // searched backwards, from a single node standing for the target in every heading
+    let headings = [
+        Complex::new(1, 0),
+        Complex::i(),
+        Complex::new(-1, 0),
+        -Complex::i(),
+    ];
+    dijkstra_reach(&None, |state: &Option<State>| match state {
+        None => headings
+            .iter()
+            .map(|dir| (Some((target, *dir)), 0))
+            .collect(),
+        Some((pos, dir)) => predecessors(pos, dir, grid, params)
+            .into_iter()
+            .map(|(state, cost)| (Some(state), cost))
+            .collect::<Vec<_>>(),
+    })
+    .filter_map(|item| Some((item.node?, item.total_cost)))
+    .collect()
+}
+
+/// The tiles on at least one path from `start` to `target` costing at most the best cost plus
+/// `delta`, with `delta` 0 giving the tiles of the best paths
+pub fn near_optimal_tiles(
+    start: Pos,
+    target: Pos,
+    grid: &Grid,
+    params: &Params,
+    delta: isize,
+) -> HashSet<Pos> {
+    let from_start = costs_from_start(start, target, grid, params);
+    let to_target = costs_to_target(target, grid, params);
+    let Some(best) = to_target.get(&(start, params.heading)) else {
+        return HashSet::default();
+    };
+    from_start
+        .iter()
+        .filter(|(state, cost)| {
+            to_target
+                .get(state)
+                .is_some_and(|rest| *cost + rest <= best + delta)
+        })
+        .map(|((pos, _), _)| *pos)
+        .collect()
+}
+
+/// The `k` cheapest distinct paths from `start` to `target` with their costs, cheapest first
+pub fn k_shortest_paths(
+    start: Pos,
+    target: Pos,
+    grid: &Grid,
+    params: &Params,
+    k: usize,
+) -> Vec<(BestPath, isize)> {
+    yen(
+        &(start, params.heading),
+        |(pos, dir)| successors(pos, dir, target, grid, params),
+        |(pos, _)| *pos == target,
+        k,
+    )
+    .into_iter()
+    .map(|(states, cost)| (BestPath::new(states), cost))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day16::parse_input;
+    use pretty_assertions::assert_eq;
+
+    const INPUT: &str = include_str!("../../fixtures/day16.txt");
+
+    #[test]
+    fn test_costs() {
+        let params = Params::default();
+        let (start, target, grid) = parse_input(INPUT).unwrap();
+        let from_start = costs_from_start(start, target, &grid, &params);
+        let to_target = costs_to_target(target, &grid, &params);
+        assert_eq!(from_start[&(start, params.heading)], 0);
+        assert_eq!(to_target[&(start, params.heading)], 7036);
+        let best = (0..4)
+            .filter_map(|i| from_start.get(&(target, Complex::i().powi(i))))
+            .min();
+        assert_eq!(best, Some(&7036));
+    }
+
+    #[test]
+    fn test_near_optimal_tiles() {
+        let params = Params::default();
+        let (start, target, grid) = parse_input(INPUT).unwrap();
+        let tiles = |delta| near_optimal_tiles(start, target, &grid, &params, delta);
+        assert_eq!(tiles(0).len(), 45);
+        assert!(tiles(0).is_subset(&tiles(2992)));
+        // the path costing 9040 only turns more on the same tiles, the ones costing 10028 do not,
+        // see test_k_shortest_paths
+        assert_eq!(tiles(2991).len(), 45);
+        assert_eq!(tiles(2992).len(), 58);
+        let params = Params::default();
+        let (start, target, grid) = parse_input("#####\n#S#E#\n#####").unwrap();
+        assert!(near_optimal_tiles(start, target, &grid, &params, 10).is_empty());
+    }
+
+    #[test]
+    fn test_k_shortest_paths() {
+        let params = Params::default();
+        let (start, target, grid) = parse_input(INPUT).unwrap();
+        let paths = k_shortest_paths(start, target, &grid, &params, 5);
+        let costs: Vec<isize> = paths.iter().map(|(_, cost)| *cost).collect();
+        assert_eq!(&costs[..3], [7036; 3]);
+        assert_eq!(&costs[3..], [9040, 10028]);
+        for (path, cost) in &paths {
+            assert_eq!((path.forwards + 1000 * path.turns) as isize, *cost);
+            assert_eq!(path.states.last().unwrap().0, target);
+        }
+        assert_eq!(
+            k_shortest_paths(start, target, &grid, &params, 1)[0],
+            paths[0]
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt::Display;
+
+use num::Complex;
+use pathfinding::prelude::astar_bag;
+use rustc_hash::FxHashSet as HashSet;
+
+use super::{successors, Dir, Element, Grid, Params};
+use crate::grid::{DenseGrid, Pos};
+
+/// A path through the maze, with its turns and forward moves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BestPath {
+    /// Every state of the reindeer from the start to the end
+    pub states: Vec<(Pos, Dir)>,
+    /// The number of quarter turns
+    pub turns: usize,
+    /// The number of tiles moved, including the moves right after a turn
+    pub forwards: usize,
+}
+
+impl BestPath {
+    pub(super) fn new(states: Vec<(Pos, Dir)>) -> Self {
+        let turns = states
+            .windows(2)
+            .map(|pair| match pair[1].1 * pair[0].1.conj() {
+                rotation if rotation == Complex::new(1, 0) => 0,
+                rotation if rotation == Complex::new(-1, 0) => 2,
+                _ => 1,
+            })
+            .sum();
+        BestPath {
+            forwards: states.len() - 1,
+            turns,
+            states,
+        }
+    }
+}
+
+/// Every optimal route through the maze, to show and check them rather than only their cost
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MazeReport {
+    pub cost: isize,
+    pub paths: Vec<BestPath>,
+    grid: Grid,
+}
+
+impl MazeReport {
+    /// The cheapest paths from `start` to `target`, `None` if the end cannot be reached
+    pub fn new(start: Pos, target: Pos, grid: &Grid, params: &Params) -> Option<Self> {
+        let min_step = params.min_step(grid);
+        let (solutions, cost) = astar_bag(
+            &(start, params.heading),
+            |(pos, dir)| successors(pos, dir, target, grid, params),
+            |(pos, _)| (target - pos).l1_norm() * min_step,
+            |(pos, _)| *pos == target,
+        )?;
+        Some(MazeReport {
+            cost,
+            paths: solutions.map(BestPath::new).collect(),
+            grid: grid.clone(),
+        })
+    }
+
+    /// The tiles on at least one of the best paths
+    pub fn tiles(&self) -> HashSet<Pos> {
+        self.paths
+            .iter()
+            .flat_map(|path| path.states.iter().map(|(pos, _)| *pos))
+            .collect()
+    }
+}
+
+/// The maze with the tiles of the best paths as `O`, over the digits too
+impl Display for MazeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let tiles = self.tiles();
+        let overlay: DenseGrid<char> = self
+            .grid
+            .iter()
+            .map(|(pos, e)| {
+                let c = match e {
+                    Element::Wall => '#',
+                    _ if tiles.contains(&pos) => 'O',
+                    Element::Empty => '.',
+                    Element::Weighted(weight) => char::from(b'0' + weight),
+                };
+                (pos, c)
+            })
+            .collect();
+        write!(f, "{overlay}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day16::parse_input;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    const INPUT: &str = include_str!("../../fixtures/day16.txt");
+
+    #[test]
+    fn test_maze_report() {
+        let params = Params::default();
+        let (start, target, grid) = parse_input(indoc! {
+            "#####
+            #..E#
+            #.#.#
+            #S..#
+            #####"
+        })
+        .unwrap();
+        let report = MazeReport::new(start, target, &grid, &params).unwrap();
+        assert_eq!(report.cost, 1004);
+        assert_eq!(
+            report.paths,
+            vec![BestPath {
+                states: vec![
+                    (Complex::new(1, 3), Complex::new(1, 0)),
+                    (Complex::new(2, 3), Complex::new(1, 0)),
+                    (Complex::new(3, 3), Complex::new(1, 0)),
+                    (Complex::new(3, 2), Complex::new(0, -1)),
+                    (Complex::new(3, 1), Complex::new(0, -1)),
+                ],
+                turns: 1,
+                forwards: 4,
+            }]
+        );
+        assert_eq!(
+            report.to_string(),
+            indoc! {"
+                #####
+                #..O#
+                #.#O#
+                #OOO#
+                #####
+            "}
+        );
+    }
+
+    #[test]
+    fn test_maze_report_example() {
+        let params = Params::default();
+        let (start, target, grid) = parse_input(INPUT).unwrap();
+        let report = MazeReport::new(start, target, &grid, &params).unwrap();
+        assert_eq!(report.cost, 7036);
+        assert_eq!(report.paths.len(), 3);
+        for path in &report.paths {
+            assert_eq!(path.forwards + 1000 * path.turns, 7036);
+            assert_eq!(path.states.last().unwrap().0, target);
+        }
+        assert_eq!(report.tiles().len(), 45);
+        assert_eq!(
+            report.to_string(),
+            indoc! {"
+                ###############
+                #.......#....O#
+                #.#.###.#.###O#
+                #.....#.#...#O#
+                #.###.#####.#O#
+                #.#.#.......#O#
+                #.#.#####.###O#
+                #..OOOOOOOOO#O#
+                ###O#O#####O#O#
+                #OOO#O....#O#O#
+                #O#O#O###.#O#O#
+                #OOOOO#...#O#O#
+                #O###.#.#.#O#O#
+                #O..#.....#OOO#
+                ###############
+            "}
+        );
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let params = Params::default();
+        let (start, target, grid) = parse_input("#####\n#S#E#\n#####").unwrap();
+        assert_eq!(MazeReport::new(start, target, &grid, &params), None);
+    }
+}
@@ -1,48 +1,54 @@
 use std::fmt::Display;
 
-type Registers = [usize; 3];
-type Program = Vec<usize>;
+use anyhow::Result;
 
-pub fn parse_input(input: &str) -> (Registers, Program) {
-    let mut lines = input.lines();
-    let register_a = lines
-        .next()
-        .unwrap()
-        .split_once(": ")
-        .unwrap()
-        .1
-        .parse()
-        .unwrap();
-    let register_b = lines
-        .next()
-        .unwrap()
-        .split_once(": ")
-        .unwrap()
-        .1
-        .parse()
-        .unwrap();
-    let register_c = lines
+use crate::parsers::{parse_at, split_once_at, ParseError};
+
+mod assembler;
+mod compiled;
+mod disassembler;
+mod symbolic;
+mod vm;
+
+pub use assembler::assemble;
+pub use compiled::{compile, Compiled};
+pub use disassembler::disassemble;
+pub use symbolic::{analyze, backtrack, Expr, LoopAnalysis};
+pub use vm::{trace_to_json, trace_to_text, Step, Stop, Vm, VmError, DEFAULT_STEP_LIMIT};
+
+pub type Registers = [usize; 3];
+pub type Program = Vec<usize>;
+
+/// The value after `": "` on the next line
+fn line_value<'a>(
+    input: &str,
+    lines: &mut impl Iterator<Item = &'a str>,
+    name: &str,
+) -> Result<&'a str, ParseError> {
+    let line = lines
         .next()
-        .unwrap()
-        .split_once(": ")
-        .unwrap()
-        .1
-        .parse()
-        .unwrap();
+        .ok_or_else(|| ParseError::at_end(input, format!("expected a line for {name}")))?;
+    Ok(split_once_at(input, line, ": ")?.1)
+}
+
+pub fn parse_input(input: &str) -> Result<(Registers, Program)> {
+    let mut lines = input.lines();
+    let register_a = parse_at(input, line_value(input, &mut lines, "register A")?)?;
+    let register_b = parse_at(input, line_value(input, &mut lines, "register B")?)?;
+    let register_c = parse_at(input, line_value(input, &mut lines, "register C")?)?;
     lines.next();
-    let program = lines
-        .next()
-        .unwrap()
-        .split_once(": ")
-        .unwrap()
-        .1
+    let program_line = line_value(input, &mut lines, "the program")?;
+    if program_line.trim().is_empty() {
+        return Err(ParseError::at(input, program_line, "the program is empty").into());
+    }
+    let program = program_line
         .split(",")
-        .map(|v| v.parse().unwrap())
-        .collect();
-    ([register_a, register_b, register_c], program)
+        .map(|v| parse_at(input, v))
+        .collect::<Result<_, _>>()?;
+    Ok(([register_a, register_b, register_c], program))
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Operand {
     Zero,
     One,
@@ -84,23 +90,36 @@ impl Operand {
         }
     }
 
-    fn combo_value(&self, registers: &Registers) -> usize {
+    /// The value of the combo operand, `None` for the reserved operand 7
+    fn combo_value(&self, registers: &Registers) -> Option<usize> {
         match self {
-            Operand::Zero => 0,
-            Operand::One => 1,
-            Operand::Two => 2,
-            Operand::Three => 3,
-            Operand::RegisterA => registers[0],
-            Operand::RegisterB => registers[1],
-            Operand::RegisterC => registers[2],
-            Operand::Invalid => panic!("Invalid operand value"),
+            Operand::Zero => Some(0),
+            Operand::One => Some(1),
+            Operand::Two => Some(2),
+            Operand::Three => Some(3),
+            Operand::RegisterA => Some(registers[0]),
+            Operand::RegisterB => Some(registers[1]),
+            Operand::RegisterC => Some(registers[2]),
+            Operand::Invalid => None,
+        }
+    }
+}
+
+/// The operand as a combo operand: a literal up to 3 or a register
+impl Display for Operand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operand::RegisterA => write!(f, "A"),
+            Operand::RegisterB => write!(f, "B"),
+            Operand::RegisterC => write!(f, "C"),
+            _ => write!(f, "{}", self.literal_value()),
         }
     }
 }
 
 #[allow(non_camel_case_types)]
-#[derive(Debug, Clone)]
-enum Instruction {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
     adv,
     bxl,
     bst,
@@ -127,34 +146,62 @@ impl From<usize> for Instruction {
     }
 }
 
+impl Instruction {
+    /// Whether the operand is read as a combo operand rather than as a literal
+    fn has_combo_operand(&self) -> bool {
+        !matches!(self, Instruction::bxl | Instruction::jnz | Instruction::bxc)
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
 fn compute(
     instruction: Instruction,
     operand: Operand,
     registers: &mut Registers,
     instruction_pointer: usize,
     outputs: &mut Vec<usize>,
-) -> usize {
-    match instruction {
+) -> Result<usize, VmError> {
+    let combo_value = |registers: &Registers| {
+        operand
+            .combo_value(registers)
+            .ok_or(VmError::InvalidOperand {
+                ip: instruction_pointer,
+                operand: operand.literal_value(),
+            })
+    };
+    let denominator = |registers: &Registers| {
+        let exponent = combo_value(registers)?;
+        u32::try_from(exponent)
+            .ok()
+            .and_then(|exponent| 2usize.checked_pow(exponent))
+            .ok_or(VmError::Overflow {
+                ip: instruction_pointer,
+                exponent,
+            })
+    };
+    let next = match instruction {
         Instruction::adv => {
             let numerator = registers[0];
-            let denominator = 2usize.pow(operand.combo_value(registers) as u32);
-            registers[0] = numerator / denominator;
+            registers[0] = numerator / denominator(registers)?;
             instruction_pointer + 2
         }
         Instruction::bdv => {
             let numerator = registers[0];
-            let denominator = 2usize.pow(operand.combo_value(registers) as u32);
-            registers[1] = numerator / denominator;
+            registers[1] = numerator / denominator(registers)?;
             instruction_pointer + 2
         }
         Instruction::cdv => {
             let numerator = registers[0];
-            let denominator = 2usize.pow(operand.combo_value(registers) as u32);
-            registers[2] = numerator / denominator;
+            registers[2] = numerator / denominator(registers)?;
             instruction_pointer + 2
         }
         Instruction::bst => {
-            registers[1] = operand.combo_value(registers) % 8;
+            registers[1] = combo_value(registers)? % 8;
             instruction_pointer + 2
         }
         Instruction::jnz => {
@@ -174,27 +221,23 @@ fn compute(
             instruction_pointer + 2
         }
         Instruction::out => {
-            outputs.push(operand.combo_value(registers) % 8);
+            outputs.push(combo_value(registers)? % 8);
             instruction_pointer + 2
         }
-    }
+    };
+    Ok(next)
 }
 
-fn execute_program(program: &Program, registers: &mut Registers) -> Vec<usize> {
-    let mut outputs = Vec::new();
-    let mut instruction_pointer = 0usize;
-    while instruction_pointer < program.len() - 1 {
-        let instruction = program[instruction_pointer].into();
-        let operand = program[instruction_pointer + 1].into();
-        instruction_pointer = compute(
-            instruction,
-            operand,
-            registers,
-            instruction_pointer,
-            &mut outputs,
-        );
-    }
-    outputs
+/// Run the program until it halts, with at most [`DEFAULT_STEP_LIMIT`] steps, see
+/// [`Vm::with_step_limit`] for another budget
+pub fn execute_program(
+    program: &Program,
+    registers: &mut Registers,
+) -> Result<Vec<usize>, VmError> {
+    let mut vm = Vm::new(program, *registers);
+    vm.run()?;
+    *registers = vm.registers;
+    Ok(vm.outputs)
 }
 
 fn _format_for_output<T: Display>(outputs: &[T]) -> String {
@@ -208,80 +251,124 @@ fn _format_for_output<T: Display>(outputs: &[T]) -> String {
 }
 
 pub fn part1((mut registers, program): (Registers, Program)) -> String {
-    let outputs = execute_program(&program, &mut registers);
-    dbg!(&registers);
+    let outputs = execute_program(&program, &mut registers)
+        .unwrap_or_else(|e| panic!("The program failed: {e}"));
     _format_for_output(&outputs)
 }
 
-fn _test_register_a(val_a: usize, registers: &mut Registers, program: &Program) -> usize {
-    registers[0] = val_a;
-    let outputs = execute_program(program, registers);
-    outputs.iter().fold(0, |acc, v| acc * 8 + v)
+/// The smallest value of register A for which the program outputs itself. Each loop of the
+/// program shifts A by 3 bits and outputs a value, so A is built 3 bits at a time from its
+/// highest bits, which are the only ones the last outputs depend on. The compiled program
+/// gives the same outputs as [`execute_program`], see `test_compiled_is_the_interpreter`.
+pub fn part2((registers, program): (Registers, Program)) -> usize {
+    // an empty program outputs itself, whatever the value of A
+    if program.is_empty() {
+        return 0;
+    }
+    let compiled = compile(&program);
+    backtrack(3, program.len(), &|index, a| {
+        let mut state = registers;
+        state[0] = a;
+        compiled.execute(&mut state).ok().as_deref() == Some(&program[index..])
+    })
+    .expect("No solution found")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use indoc::indoc;
     use pretty_assertions::assert_eq;
 
-    const INPUT: &str = indoc! {
-        "Register A: 729
-        Register B: 0
-        Register C: 0
-
-        Program: 0,1,5,4,3,0
-        "
-    };
-    const INPUT_P2: &str = indoc! {
-        "Register A: 2024
-        Register B: 0
-        Register C: 0
-
-        Program: 0,3,5,4,3,0"
-    };
+    const INPUT: &str = include_str!("../fixtures/day17.txt");
+    const INPUT_P2: &str = include_str!("../fixtures/day17_p2.txt");
 
     #[test]
     fn test_parse_input() {
-        let (registers, program) = parse_input(INPUT);
+        let (registers, program) = parse_input(INPUT).unwrap();
         assert_eq!(registers, [729, 0, 0]);
         assert_eq!(program, Vec::from_iter([0, 1, 5, 4, 3, 0]));
     }
 
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("Register A: 729\nRegister B: 0\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 1: expected a line for register C"
+        );
+        let e = parse_input("Register A: 729\nRegister B: x\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 13: invalid value \"x\": invalid digit found in string"
+        );
+        let e =
+            parse_input("Register A: 0\nRegister B: 0\nRegister C: 0\n\nProgram: ").unwrap_err();
+        assert_eq!(e.to_string(), "line 5, column 10: the program is empty");
+    }
+
+    #[test]
+    fn test_part2_empty_program() {
+        assert_eq!(part2(([0, 0, 0], vec![])), 0);
+    }
+
     #[test]
     fn test_compute() {
         let mut registers = [0, 0, 9];
         let program = vec![2, 6];
-        let outputs = execute_program(&program, &mut registers);
+        let outputs = execute_program(&program, &mut registers).unwrap();
         assert!(outputs.is_empty());
         assert_eq!(registers[1], 1);
 
         let mut registers = [10, 0, 9];
         let program = vec![5, 0, 5, 1, 5, 4];
-        let outputs = execute_program(&program, &mut registers);
+        let outputs = execute_program(&program, &mut registers).unwrap();
         assert_eq!(outputs, vec![0, 1, 2]);
 
         let mut registers = [2024, 0, 9];
         let program = vec![0, 1, 5, 4, 3, 0];
-        let outputs = execute_program(&program, &mut registers);
+        let outputs = execute_program(&program, &mut registers).unwrap();
         assert_eq!(outputs, vec![4, 2, 5, 6, 7, 7, 7, 7, 3, 1, 0]);
         assert_eq!(registers[0], 0);
 
         let mut registers = [2024, 29, 9];
         let program = vec![1, 7];
-        let _outputs = execute_program(&program, &mut registers);
+        execute_program(&program, &mut registers).unwrap();
         assert_eq!(registers[1], 26);
 
         let mut registers = [0, 2024, 43690];
         let program = vec![4, 0];
-        let _outputs = execute_program(&program, &mut registers);
+        execute_program(&program, &mut registers).unwrap();
         assert_eq!(registers[1], 44354);
     }
 
+    #[test]
+    fn test_execute_program_error() {
+        let mut registers = [1, 0, 0];
+        assert_eq!(
+            execute_program(&vec![3, 0], &mut registers),
+            Err(VmError::StepLimitExceeded {
+                limit: DEFAULT_STEP_LIMIT
+            })
+        );
+        assert_eq!(
+            execute_program(&vec![5, 7], &mut registers),
+            Err(VmError::InvalidOperand { ip: 0, operand: 7 })
+        );
+    }
+
     #[test]
     fn test_part1() {
-        let (registers, program) = parse_input(INPUT);
+        let (registers, program) = parse_input(INPUT).unwrap();
         let outputs = part1((registers, program));
         assert_eq!(outputs, "4,6,3,5,6,3,5,2,1,0".to_string())
     }
+
+    #[test]
+    fn test_part2() {
+        let (registers, program) = parse_input(INPUT_P2).unwrap();
+        assert_eq!(part2((registers, program.clone())), 117440);
+
+        let mut registers = [117440, 0, 0];
+        assert_eq!(execute_program(&program, &mut registers).unwrap(), program);
+    }
 }
@@ -0,0 +1,204 @@
+use rustc_hash::FxHashMap as HashMap;
+
+use super::{Instruction, Program};
+use crate::parsers::{parse_at, ParseError};
+
+/// An instruction before its labels are resolved, with its slices of the source
+struct Line<'a> {
+    instruction: Instruction,
+    mnemonic: &'a str,
+    operand: &'a str,
+}
+
+fn is_label(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_instruction<'a>(source: &str, code: &'a str) -> Result<Line<'a>, ParseError> {
+    let mut tokens = code.split_whitespace();
+    let mnemonic = tokens.next().unwrap_or(code);
+    let instruction = (0..8)
+        .map(Instruction::from)
+        .find(|instruction| instruction.to_string() == mnemonic)
+        .ok_or_else(|| {
+            ParseError::at(source, mnemonic, format!("unknown mnemonic {mnemonic:?}"))
+        })?;
+    let operand = tokens.next().ok_or_else(|| {
+        ParseError::at(source, mnemonic, format!("missing operand for {mnemonic}"))
+    })?;
+    if let Some(extra) = tokens.next() {
+        return Err(ParseError::at(
+            source,
+            extra,
+            format!("unexpected {extra:?} after the operand"),
+        ));
+    }
+    Ok(Line {
+        instruction,
+        mnemonic,
+        operand,
+    })
+}
+
+/// The value of the operand of `line`, with the labels resolved
+fn operand_value(
+    source: &str,
+    line: &Line,
+    labels: &HashMap<&str, usize>,
+) -> Result<usize, ParseError> {
+    let operand = line.operand;
+    let error = |message: String| ParseError::at(source, operand, message);
+    if line.instruction.has_combo_operand() {
+        return match operand {
+            "A" => Ok(4),
+            "B" => Ok(5),
+            "C" => Ok(6),
+            _ => match parse_at(source, operand)? {
+                value @ 0..=3 => Ok(value),
+                value => Err(error(format!(
+                    "invalid combo operand {value} for {}, expected 0 to 3, A, B or C",
+                    line.mnemonic
+                ))),
+            },
+        };
+    }
+    let value = match labels.get(operand) {
+        Some(&ip) if ip > 7 => {
+            return Err(error(format!(
+                "label {operand:?} is at {ip}, out of the reach of jnz"
+            )))
+        }
+        Some(&ip) => ip,
+        None if line.instruction == Instruction::jnz && is_label(operand) => {
+            return Err(error(format!("unknown label {operand:?}")))
+        }
+        None => parse_at(source, operand)?,
+    };
+    if value > 7 {
+        return Err(error(format!(
+            "operand {value} of {} does not fit in 3 bits",
+            line.mnemonic
+        )));
+    }
+    if line.instruction == Instruction::jnz && value % 2 == 1 {
+        return Err(error(format!("jump target {value} is at an odd offset")));
+    }
+    Ok(value)
+}
+
+/// Assemble one instruction per line, such as `adv 3`, `out A` or `jnz loop`, into a
+/// program. A line can start with a `label:` and anything after a `;` is a comment, so the
+/// output of [`super::disassemble`] assembles back into the same program, as long as it is
+/// valid: a `jnz` to an odd or out of range target, the combo operand 7, invalid opcodes and a
+/// trailing value do not round-trip.
+pub fn assemble(source: &str) -> Result<Program, ParseError> {
+    let mut labels = HashMap::default();
+    let mut lines = vec![];
+    for line in source.lines() {
+        let mut code = line.split(';').next().unwrap_or(line).trim();
+        if let Some((label, rest)) = code.split_once(':') {
+            let label = label.trim();
+            if !is_label(label) {
+                return Err(ParseError::at(
+                    source,
+                    label,
+                    format!("invalid label {label:?}"),
+                ));
+            }
+            if labels.insert(label, 2 * lines.len()).is_some() {
+                return Err(ParseError::at(
+                    source,
+                    label,
+                    format!("duplicate label {label:?}"),
+                ));
+            }
+            code = rest.trim();
+        }
+        if !code.is_empty() {
+            lines.push(parse_instruction(source, code)?);
+        }
+    }
+
+    let mut program = Vec::with_capacity(2 * lines.len());
+    for line in &lines {
+        program.push(line.instruction as usize);
+        program.push(operand_value(source, line, &labels)?);
+    }
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day17::disassemble;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_assemble() {
+        let source = indoc! {"
+            ; output A, one octal digit at a time
+            loop:
+                adv 1
+                out A  ; the lowest 3 bits
+                jnz loop
+        "};
+        assert_eq!(assemble(source).unwrap(), vec![0, 1, 5, 4, 3, 0]);
+        assert_eq!(
+            assemble("bst C\nbxl 7\nstart: bxc 0\njnz start").unwrap(),
+            vec![2, 6, 1, 7, 4, 0, 3, 4]
+        );
+    }
+
+    #[test]
+    fn test_assemble_disassembled() {
+        for program in [
+            vec![0, 1, 5, 4, 3, 0],
+            vec![0, 3, 5, 4, 3, 0],
+            vec![2, 4, 1, 5, 7, 5, 4, 3, 0, 3, 5, 5, 3, 0],
+            vec![3, 2, 5, 6, 6, 5, 3, 6],
+        ] {
+            assert_eq!(assemble(&disassemble(&program)).unwrap(), program);
+        }
+    }
+
+    #[test]
+    fn test_assemble_error() {
+        let error = |source| assemble(source).unwrap_err().to_string();
+        assert_eq!(
+            error("adv 1\nout 7"),
+            "line 2, column 5: invalid combo operand 7 for out, expected 0 to 3, A, B or C"
+        );
+        assert_eq!(
+            error("adv 1\nbxl 3\nout A\njnz 3"),
+            "line 4, column 5: jump target 3 is at an odd offset"
+        );
+        assert_eq!(
+            error("adv 1\nout A\nadv 1\nout A\nend: jnz end"),
+            "line 5, column 10: label \"end\" is at 8, out of the reach of jnz"
+        );
+        assert_eq!(
+            error("jnz nowhere"),
+            "line 1, column 5: unknown label \"nowhere\""
+        );
+        assert_eq!(
+            error("a: adv 1\na: out A"),
+            "line 2, column 1: duplicate label \"a\""
+        );
+        assert_eq!(
+            error("adv 1\n  mul 2"),
+            "line 2, column 3: unknown mnemonic \"mul\""
+        );
+        assert_eq!(error("out"), "line 1, column 1: missing operand for out");
+        assert_eq!(
+            error("out A B"),
+            "line 1, column 7: unexpected \"B\" after the operand"
+        );
+        assert_eq!(
+            error("bxl 8"),
+            "line 1, column 5: operand 8 of bxl does not fit in 3 bits"
+        );
+        assert!(error("bxl x").starts_with("line 1, column 5: invalid value \"x\""));
+    }
+}
@@ -0,0 +1,178 @@
+use super::{Instruction, Operand, Program, Registers, VmError, DEFAULT_STEP_LIMIT};
+
+/// A decoded combo operand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combo {
+    Literal(usize),
+    Register(usize),
+    Invalid,
+}
+
+impl From<Operand> for Combo {
+    fn from(operand: Operand) -> Self {
+        match operand {
+            Operand::RegisterA => Combo::Register(0),
+            Operand::RegisterB => Combo::Register(1),
+            Operand::RegisterC => Combo::Register(2),
+            Operand::Invalid => Combo::Invalid,
+            operand => Combo::Literal(operand.literal_value()),
+        }
+    }
+}
+
+/// An instruction with its operand resolved. `Dv` is `adv`, `bdv` and `cdv`, with the register
+/// the result goes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Dv(usize, Combo),
+    Bxl(usize),
+    Bst(Combo),
+    Jnz(usize),
+    Bxc,
+    Out(Combo),
+    InvalidOpcode(usize),
+    InvalidOperand(usize),
+}
+
+/// A program decoded once, to be executed many times
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compiled {
+    /// The instruction starting at each instruction pointer, odd ones included since a jump
+    /// can land on them
+    ops: Vec<Op>,
+    step_limit: usize,
+}
+
+fn decode(opcode: usize, operand: usize) -> Op {
+    if opcode > 7 {
+        return Op::InvalidOpcode(opcode);
+    }
+    if operand > 7 {
+        return Op::InvalidOperand(operand);
+    }
+    let combo = Combo::from(Operand::from(operand));
+    match Instruction::from(opcode) {
+        Instruction::adv => Op::Dv(0, combo),
+        Instruction::bdv => Op::Dv(1, combo),
+        Instruction::cdv => Op::Dv(2, combo),
+        Instruction::bxl => Op::Bxl(operand),
+        Instruction::bst => Op::Bst(combo),
+        Instruction::jnz => Op::Jnz(operand),
+        Instruction::bxc => Op::Bxc,
+        Instruction::out => Op::Out(combo),
+    }
+}
+
+/// Decode `program`, which is then executed with the same results as
+/// [`super::execute_program`]
+pub fn compile(program: &Program) -> Compiled {
+    Compiled {
+        ops: program
+            .windows(2)
+            .map(|pair| decode(pair[0], pair[1]))
+            .collect(),
+        step_limit: DEFAULT_STEP_LIMIT,
+    }
+}
+
+impl Compiled {
+    /// Fail with [`VmError::StepLimitExceeded`] rather than executing more than `limit` steps
+    pub fn with_step_limit(mut self, limit: usize) -> Self {
+        self.step_limit = limit;
+        self
+    }
+
+    pub fn execute(&self, registers: &mut Registers) -> Result<Vec<usize>, VmError> {
+        let mut outputs = vec![];
+        let mut state = *registers;
+        let mut ip = 0;
+        let mut steps = 0;
+        while let Some(&op) = self.ops.get(ip) {
+            if steps == self.step_limit {
+                return Err(VmError::StepLimitExceeded {
+                    limit: self.step_limit,
+                });
+            }
+            let combo = |combo: Combo, state: &Registers| match combo {
+                Combo::Literal(value) => Ok(value),
+                Combo::Register(register) => Ok(state[register]),
+                Combo::Invalid => Err(VmError::InvalidOperand { ip, operand: 7 }),
+            };
+            let mut next = ip + 2;
+            match op {
+                Op::Dv(register, operand) => {
+                    let exponent = combo(operand, &state)?;
+                    if exponent >= usize::BITS as usize {
+                        return Err(VmError::Overflow { ip, exponent });
+                    }
+                    state[register] = state[0] >> exponent;
+                }
+                Op::Bxl(value) => state[1] ^= value,
+                Op::Bst(operand) => state[1] = combo(operand, &state)? % 8,
+                Op::Jnz(target) if state[0] != 0 => next = target,
+                Op::Jnz(_) => {}
+                Op::Bxc => state[1] ^= state[2],
+                Op::Out(operand) => outputs.push(combo(operand, &state)? % 8),
+                Op::InvalidOpcode(opcode) => return Err(VmError::InvalidOpcode { ip, opcode }),
+                Op::InvalidOperand(operand) => return Err(VmError::InvalidOperand { ip, operand }),
+            }
+            ip = next;
+            steps += 1;
+        }
+        *registers = state;
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day17::{assemble, execute_program};
+    use pretty_assertions::assert_eq;
+
+    /// The cases of `test_compute`, and programs that fail
+    fn cases() -> Vec<(Registers, Program)> {
+        vec![
+            ([0, 0, 9], vec![2, 6]),
+            ([10, 0, 9], vec![5, 0, 5, 1, 5, 4]),
+            ([2024, 0, 9], vec![0, 1, 5, 4, 3, 0]),
+            ([2024, 29, 9], vec![1, 7]),
+            ([0, 2024, 43690], vec![4, 0]),
+            ([729, 0, 0], vec![0, 1, 5, 4, 3, 0]),
+            ([117440, 0, 0], vec![0, 3, 5, 4, 3, 0]),
+            (
+                [1 << 40, 0, 0],
+                vec![2, 4, 1, 5, 7, 5, 4, 3, 0, 3, 5, 5, 3, 0],
+            ),
+            ([6, 0, 0], vec![3, 3, 5, 0, 3, 5, 4, 3, 0, 1, 3, 0]),
+            ([1, 0, 0], vec![3, 0]),
+            ([1, 0, 0], vec![0, 1, 5, 7]),
+            ([1, 0, 0], vec![9, 0]),
+            ([1, 0, 0], vec![5, 9]),
+            ([1, 64, 0], vec![7, 5]),
+        ]
+    }
+
+    #[test]
+    fn test_compiled_is_the_interpreter() {
+        for (registers, program) in cases() {
+            let mut expected_registers = registers;
+            let expected = execute_program(&program, &mut expected_registers);
+            let mut compiled_registers = registers;
+            let compiled = compile(&program).execute(&mut compiled_registers);
+            assert_eq!(compiled, expected, "{program:?} with {registers:?}");
+            assert_eq!(compiled_registers, expected_registers);
+        }
+    }
+
+    #[test]
+    fn test_step_limit() {
+        let program = assemble("adv 1\nout A\njnz 0").unwrap();
+        let compiled = compile(&program).with_step_limit(10);
+        assert_eq!(compiled.execute(&mut [7, 0, 0]), Ok(vec![3, 1, 0]));
+        assert_eq!(
+            compiled.execute(&mut [1 << 10, 0, 0]),
+            Err(VmError::StepLimitExceeded { limit: 10 })
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::BTreeSet;
+
+use super::{Instruction, Operand, Program};
+
+/// The jump targets that start an instruction, which get a label
+fn labels(program: &Program) -> BTreeSet<usize> {
+    program
+        .chunks_exact(2)
+        .filter(|chunk| chunk[0] <= 7 && Instruction::from(chunk[0]) == Instruction::jnz)
+        .map(|chunk| chunk[1])
+        .filter(|&target| target % 2 == 0 && target + 1 < program.len())
+        .collect()
+}
+
+/// What the instruction does, in pseudo code
+fn describe(instruction: Instruction, operand: &str) -> String {
+    match instruction {
+        Instruction::adv => format!("A = A >> {operand}"),
+        Instruction::bdv => format!("B = A >> {operand}"),
+        Instruction::cdv => format!("C = A >> {operand}"),
+        Instruction::bxl => format!("B = B ^ {operand}"),
+        Instruction::bst => format!("B = {operand} % 8"),
+        Instruction::jnz => format!("jump to {operand} if A != 0"),
+        Instruction::bxc => "B = B ^ C".to_string(),
+        Instruction::out => format!("output {operand} % 8"),
+    }
+}
+
+/// The program as one mnemonic and operand per line, with the combo operands resolved to
+/// registers or literals and the jump targets labelled `L{ip}:`
+pub fn disassemble(program: &Program) -> String {
+    let labels = labels(program);
+    let mut lines = vec![];
+    for (index, chunk) in program.chunks(2).enumerate() {
+        let ip = 2 * index;
+        let &[opcode, value] = chunk else {
+            lines.push(format!("    ; trailing value {}", chunk[0]));
+            break;
+        };
+        if labels.contains(&ip) {
+            lines.push(format!("L{ip}:"));
+        }
+        if opcode > 7 {
+            lines.push(format!("    ; invalid opcode {opcode}"));
+            continue;
+        }
+        if value > 7 {
+            lines.push(format!("    ; invalid operand {value}"));
+            continue;
+        }
+        let instruction = Instruction::from(opcode);
+        let operand = match instruction {
+            Instruction::jnz if labels.contains(&value) => format!("L{value}"),
+            _ if instruction.has_combo_operand() => Operand::from(value).to_string(),
+            _ => value.to_string(),
+        };
+        let description = if instruction.has_combo_operand() && value == 7 {
+            "invalid combo operand".to_string()
+        } else {
+            describe(instruction, &operand)
+        };
+        let code = format!("{instruction} {operand}");
+        lines.push(format!("    {code:<8}; {description}"));
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_disassemble() {
+        assert_eq!(
+            disassemble(&vec![0, 1, 5, 4, 3, 0]),
+            indoc! {"
+                L0:
+                    adv 1   ; A = A >> 1
+                    out A   ; output A % 8
+                    jnz L0  ; jump to L0 if A != 0
+            "}
+        );
+        assert_eq!(
+            disassemble(&vec![2, 4, 1, 5, 7, 5, 4, 3, 0, 3, 5, 5, 3, 0]),
+            indoc! {"
+                L0:
+                    bst A   ; B = A % 8
+                    bxl 5   ; B = B ^ 5
+                    cdv B   ; C = A >> B
+                    bxc 3   ; B = B ^ C
+                    adv 3   ; A = A >> 3
+                    out B   ; output B % 8
+                    jnz L0  ; jump to L0 if A != 0
+            "}
+        );
+    }
+
+    #[test]
+    fn test_disassemble_invalid() {
+        assert_eq!(
+            disassemble(&vec![3, 3, 5, 7, 3, 2]),
+            indoc! {"
+                    jnz 3   ; jump to 3 if A != 0
+                L2:
+                    out 7   ; invalid combo operand
+                    jnz L2  ; jump to L2 if A != 0
+            "}
+        );
+        assert_eq!(
+            disassemble(&vec![5, 4, 0]),
+            "    out A   ; output A % 8\n    ; trailing value 0\n"
+        );
+        assert_eq!(
+            disassemble(&vec![8, 0, 3, 2, 5, 9]),
+            indoc! {"
+                    ; invalid opcode 8
+                L2:
+                    jnz L2  ; jump to L2 if A != 0
+                    ; invalid operand 9
+            "}
+        );
+    }
+}
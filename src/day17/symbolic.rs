@@ -0,0 +1,391 @@
+use std::fmt::Display;
+
+use anyhow::{bail, Result};
+
+use super::{Instruction, Operand, Program};
+
+/// A value computed by the program, as a function of register A at the start of an iteration
+/// of its loop. `B` and `C` are the values left by the previous iteration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    A,
+    B,
+    C,
+    Const(usize),
+    Shr(Box<Expr>, Box<Expr>),
+    Xor(Box<Expr>, Box<Expr>),
+    Mod8(Box<Expr>),
+}
+
+impl Expr {
+    fn shr(value: Expr, shift: Expr) -> Expr {
+        match (value, shift) {
+            (value, Expr::Const(0)) => value,
+            (Expr::Const(value), Expr::Const(shift)) => Expr::Const(shr(value, shift)),
+            (Expr::Shr(value, first), Expr::Const(shift)) => match *first {
+                Expr::Const(first) => Expr::Shr(value, Box::new(Expr::Const(first + shift))),
+                first => Expr::Shr(
+                    Box::new(Expr::Shr(value, Box::new(first))),
+                    Box::new(Expr::Const(shift)),
+                ),
+            },
+            (value, shift) => Expr::Shr(Box::new(value), Box::new(shift)),
+        }
+    }
+
+    fn xor(left: Expr, right: Expr) -> Expr {
+        match (left, right) {
+            (value, Expr::Const(0)) | (Expr::Const(0), value) => value,
+            (Expr::Const(left), Expr::Const(right)) => Expr::Const(left ^ right),
+            (left, right) => Expr::Xor(Box::new(left), Box::new(right)),
+        }
+    }
+
+    fn mod8(value: Expr) -> Expr {
+        match value {
+            Expr::Const(value) => Expr::Const(value % 8),
+            Expr::Mod8(value) => Expr::Mod8(value),
+            value => Expr::Mod8(Box::new(value)),
+        }
+    }
+
+    /// Whether the value depends on what the previous iteration left in B or C
+    fn uses_previous_iteration(&self) -> bool {
+        match self {
+            Expr::B | Expr::C => true,
+            Expr::A | Expr::Const(_) => false,
+            Expr::Mod8(value) => value.uses_previous_iteration(),
+            Expr::Shr(left, right) | Expr::Xor(left, right) => {
+                left.uses_previous_iteration() || right.uses_previous_iteration()
+            }
+        }
+    }
+
+    /// The largest possible value, `None` if it depends on all of A
+    fn max_value(&self) -> Option<usize> {
+        match self {
+            Expr::A | Expr::B | Expr::C => None,
+            Expr::Const(value) => Some(*value),
+            Expr::Mod8(_) => Some(7),
+            Expr::Shr(value, _) => value.max_value(),
+            Expr::Xor(left, right) => {
+                let max = left.max_value()?.max(right.max_value()?);
+                Some((max + 1).next_power_of_two() - 1)
+            }
+        }
+    }
+
+    /// The number of low bits of A needed to compute the `bits` low bits of the value, `None`
+    /// if it depends on all of A
+    fn window(&self, bits: u32) -> Option<u32> {
+        match self {
+            Expr::A => Some(bits),
+            Expr::B | Expr::C | Expr::Const(_) => Some(0),
+            Expr::Mod8(value) => value.window(bits.min(3)),
+            Expr::Xor(left, right) => Some(left.window(bits)?.max(right.window(bits)?)),
+            Expr::Shr(value, shift) => {
+                let max_shift = shift.max_value()?;
+                let shift_bits = usize::BITS - max_shift.leading_zeros();
+                let value_bits = value.window(bits.saturating_add(max_shift.try_into().ok()?))?;
+                Some(value_bits.max(shift.window(shift_bits)?))
+            }
+        }
+    }
+
+    /// The value when A is `a`, `None` if it depends on what the previous iteration left in B
+    /// or C
+    pub fn eval(&self, a: usize) -> Option<usize> {
+        match self {
+            Expr::A => Some(a),
+            Expr::B | Expr::C => None,
+            Expr::Const(value) => Some(*value),
+            Expr::Shr(value, shift) => Some(shr(value.eval(a)?, shift.eval(a)?)),
+            Expr::Xor(left, right) => Some(left.eval(a)? ^ right.eval(a)?),
+            Expr::Mod8(value) => Some(value.eval(a)? % 8),
+        }
+    }
+}
+
+/// `value / 2^shift`, which is 0 for shifts past the width of the value
+fn shr(value: usize, shift: usize) -> usize {
+    u32::try_from(shift)
+        .ok()
+        .and_then(|shift| value.checked_shr(shift))
+        .unwrap_or(0)
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::A => write!(f, "A"),
+            Expr::B => write!(f, "B"),
+            Expr::C => write!(f, "C"),
+            Expr::Const(value) => write!(f, "{value}"),
+            Expr::Shr(value, shift) => write!(f, "({value} >> {shift})"),
+            Expr::Xor(left, right) => write!(f, "({left} ^ {right})"),
+            Expr::Mod8(value) => write!(f, "({value} % 8)"),
+        }
+    }
+}
+
+/// A program made of a single loop, which outputs values that only depend on A and then
+/// shifts A right until it is 0
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopAnalysis {
+    /// The number of bits A is shifted by on each iteration
+    pub shift: u32,
+    /// The values output by an iteration
+    pub outputs: Vec<Expr>,
+    /// The number of low bits of A the outputs of an iteration depend on
+    pub window: u32,
+}
+
+impl Display for LoopAnalysis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "while A != 0, using the low {} bits of A:", self.window)?;
+        for output in &self.outputs {
+            writeln!(f, "    out {output}")?;
+        }
+        write!(f, "    A = A >> {}", self.shift)
+    }
+}
+
+/// Execute the body of the loop of `program` with A as a variable
+pub fn analyze(program: &Program) -> Result<LoopAnalysis> {
+    let Some((body, [3, 0])) = program.split_last_chunk() else {
+        bail!("the program does not end with jnz 0");
+    };
+    if !body.len().is_multiple_of(2) {
+        bail!("the program has an odd length");
+    }
+    let mut registers = [Expr::A, Expr::B, Expr::C];
+    let mut outputs = vec![];
+    for (index, chunk) in body.chunks_exact(2).enumerate() {
+        let ip = 2 * index;
+        let &[opcode, operand] = chunk else {
+            unreachable!()
+        };
+        if opcode > 7 || operand > 7 {
+            bail!("invalid instruction {opcode},{operand} at ip {ip}");
+        }
+        let instruction = Instruction::from(opcode);
+        let value = if instruction.has_combo_operand() {
+            match Operand::from(operand) {
+                Operand::RegisterA => registers[0].clone(),
+                Operand::RegisterB => registers[1].clone(),
+                Operand::RegisterC => registers[2].clone(),
+                Operand::Invalid => bail!("invalid combo operand 7 at ip {ip}"),
+                operand => Expr::Const(operand.literal_value()),
+            }
+        } else {
+            Expr::Const(operand)
+        };
+        match instruction {
+            Instruction::adv => registers[0] = Expr::shr(registers[0].clone(), value),
+            Instruction::bdv => registers[1] = Expr::shr(registers[0].clone(), value),
+            Instruction::cdv => registers[2] = Expr::shr(registers[0].clone(), value),
+            Instruction::bst => registers[1] = Expr::mod8(value),
+            Instruction::bxl => registers[1] = Expr::xor(registers[1].clone(), value),
+            Instruction::bxc => {
+                registers[1] = Expr::xor(registers[1].clone(), registers[2].clone())
+            }
+            Instruction::out => outputs.push(Expr::mod8(value)),
+            Instruction::jnz => bail!("the loop has a jump at ip {ip}"),
+        }
+    }
+
+    let shift = match &registers[0] {
+        Expr::Shr(a, shift) if **a == Expr::A => match **shift {
+            Expr::Const(shift @ 1..64) => shift as u32,
+            _ => bail!("A is shifted by {shift} instead of a constant on each iteration"),
+        },
+        a => bail!("A = {a} instead of a constant shift of A on each iteration"),
+    };
+    if outputs.is_empty() {
+        bail!("the loop outputs nothing");
+    }
+    if let Some(output) = outputs.iter().find(|o| o.uses_previous_iteration()) {
+        bail!("out {output} depends on the previous iteration");
+    }
+    let window = outputs
+        .iter()
+        .map(|output| output.window(3))
+        .try_fold(0, |window, bits| Some(window.max(bits?)));
+    let Some(window) = window else {
+        bail!("the outputs depend on all the bits of A");
+    };
+    Ok(LoopAnalysis {
+        shift,
+        outputs,
+        window,
+    })
+}
+
+/// Find the smallest value built `bits` at a time, from the most significant digit, for which
+/// `accept(level, value)` holds for the values made of the first digits, from `levels - 1`
+/// down to 0
+pub fn backtrack(
+    bits: u32,
+    levels: usize,
+    accept: &impl Fn(usize, usize) -> bool,
+) -> Option<usize> {
+    fn search(
+        bits: u32,
+        level: usize,
+        value: usize,
+        accept: &impl Fn(usize, usize) -> bool,
+    ) -> Option<usize> {
+        (0..1 << bits).find_map(|digit| {
+            let candidate = value.checked_mul(1 << bits)? + digit;
+            if !accept(level, candidate) {
+                None
+            } else if level == 0 {
+                Some(candidate)
+            } else {
+                search(bits, level - 1, candidate, accept)
+            }
+        })
+    }
+    match levels {
+        0 => None,
+        _ => search(bits, levels - 1, 0, accept),
+    }
+}
+
+impl LoopAnalysis {
+    /// The smallest value of A for which the program outputs `expected`. The iteration `i`
+    /// starts with `A >> (shift * i)`, which is not 0 for the iterations after the first.
+    pub fn solve(&self, expected: &[usize]) -> Option<usize> {
+        if !expected.len().is_multiple_of(self.outputs.len()) {
+            return None;
+        }
+        let iterations = expected.len() / self.outputs.len();
+        backtrack(self.shift, iterations, &|iteration, a| {
+            let start = iteration * self.outputs.len();
+            (iteration == 0 || a != 0)
+                && self
+                    .outputs
+                    .iter()
+                    .zip(&expected[start..])
+                    .all(|(output, &value)| output.eval(a) == Some(value))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day17::{assemble, execute_program};
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    /// A program like the puzzle inputs
+    const PROGRAM: &str = indoc! {"
+        loop:
+            bst A
+            bxl 5
+            cdv B
+            bxl 6
+            adv 3
+            bxc 0
+            out B
+            jnz loop
+    "};
+
+    #[test]
+    fn test_analyze() {
+        let analysis = analyze(&vec![0, 3, 5, 4, 3, 0]).unwrap();
+        assert_eq!(
+            analysis,
+            LoopAnalysis {
+                shift: 3,
+                outputs: vec![Expr::Mod8(Box::new(Expr::Shr(
+                    Box::new(Expr::A),
+                    Box::new(Expr::Const(3))
+                )))],
+                window: 6,
+            }
+        );
+
+        let analysis = analyze(&assemble(PROGRAM).unwrap()).unwrap();
+        assert_eq!(
+            analysis.to_string(),
+            indoc! {"
+                while A != 0, using the low 10 bits of A:
+                    out (((((A % 8) ^ 5) ^ 6) ^ (A >> ((A % 8) ^ 5))) % 8)
+                    A = A >> 3"}
+        );
+        for a in [0, 7, 1234, 117440, 1 << 40] {
+            let mut registers = [a, 0, 0];
+            let outputs = execute_program(&assemble(PROGRAM).unwrap(), &mut registers).unwrap();
+            assert_eq!(analysis.outputs[0].eval(a), Some(outputs[0]));
+            assert_eq!(
+                analysis.outputs[0].eval(a % (1 << analysis.window)),
+                Some(outputs[0])
+            );
+        }
+    }
+
+    #[test]
+    fn test_eval_previous_iteration() {
+        // B is xored with 1 on each iteration, starting from what the previous one left
+        let program = assemble("bxl 1\nout B\nadv 1\njnz 0").unwrap();
+        let outputs = execute_program(&program, &mut [3, 0, 0]).unwrap();
+        assert_eq!(outputs, vec![1, 0]);
+        let output = Expr::mod8(Expr::xor(Expr::B, Expr::Const(1)));
+        assert_eq!(output.to_string(), "((B ^ 1) % 8)");
+        assert_eq!(output.eval(3), None);
+        assert_eq!(output.eval(1), None);
+        assert_eq!(Expr::shr(Expr::A, Expr::Const(1)).eval(3), Some(1));
+    }
+
+    #[test]
+    fn test_analyze_error() {
+        let error = |source| analyze(&assemble(source).unwrap()).unwrap_err().to_string();
+        assert_eq!(error("out A"), "the program does not end with jnz 0");
+        assert_eq!(
+            error("bxl 1\nout B\nadv 1\njnz 0"),
+            "out ((B ^ 1) % 8) depends on the previous iteration"
+        );
+        assert_eq!(
+            error("bst A\nadv B\nout A\njnz 0"),
+            "A is shifted by (A % 8) instead of a constant on each iteration"
+        );
+        assert_eq!(
+            error("out A\nbdv 1\njnz 0"),
+            "A = A instead of a constant shift of A on each iteration"
+        );
+        assert_eq!(
+            error("adv 3\nbst A\ncdv B\nbdv C\nout B\njnz 0"),
+            "the outputs depend on all the bits of A"
+        );
+    }
+
+    #[test]
+    fn test_backtrack() {
+        let accept = |level: usize, value: usize| value == 0xabc >> (4 * level);
+        assert_eq!(backtrack(4, 3, &accept), Some(0xabc));
+        assert_eq!(backtrack(4, 0, &accept), None);
+        // the prefixes of 0x102 are divisible by their number of digits
+        let accept = |level: usize, value: usize| value > 0 && value.is_multiple_of(3 - level);
+        assert_eq!(backtrack(4, 3, &accept), Some(0x102));
+        // 1 followed by 22 digits 0 does not fit in 64 bits
+        let accept =
+            |level: usize, value| 1usize.checked_shl(3 * (22 - level) as u32) == Some(value);
+        assert_eq!(backtrack(3, 23, &accept), None);
+    }
+
+    #[test]
+    fn test_solve() {
+        let program = vec![0, 3, 5, 4, 3, 0];
+        assert_eq!(analyze(&program).unwrap().solve(&program), Some(117440));
+
+        let program = assemble(PROGRAM).unwrap();
+        let analysis = analyze(&program).unwrap();
+        let expected = execute_program(&program, &mut [0o7654321, 0, 0]).unwrap();
+        let a = analysis.solve(&expected).unwrap();
+        assert!(a <= 0o7654321);
+        assert_eq!(execute_program(&program, &mut [a, 0, 0]).unwrap(), expected);
+        assert_eq!(analysis.solve(&[8]), None);
+    }
+}
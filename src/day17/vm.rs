@@ -0,0 +1,339 @@
+use std::collections::BTreeSet;
+use std::fmt::Display;
+
+use itertools::Itertools;
+
+use super::{compute, Instruction, Operand, Program, Registers};
+
+/// One executed instruction, with the registers around it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub ip: usize,
+    pub instruction: Instruction,
+    pub operand: usize,
+    pub before: Registers,
+    pub after: Registers,
+    pub output: Option<usize>,
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operand = if self.instruction.has_combo_operand() {
+            Operand::from(self.operand).to_string()
+        } else {
+            self.operand.to_string()
+        };
+        let [a, b, c] = self.before;
+        let [a2, b2, c2] = self.after;
+        write!(
+            f,
+            "{:>3}: {} {operand}  A={a} B={b} C={c} -> A={a2} B={b2} C={c2}",
+            self.ip, self.instruction
+        )?;
+        if let Some(output) = self.output {
+            write!(f, "  out {output}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The default number of steps after which a program is considered stuck in a loop
+pub const DEFAULT_STEP_LIMIT: usize = 1_000_000;
+
+/// Why the program could not run to completion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VmError {
+    InvalidOpcode { ip: usize, opcode: usize },
+    InvalidOperand { ip: usize, operand: usize },
+    Overflow { ip: usize, exponent: usize },
+    StepLimitExceeded { limit: usize },
+}
+
+impl Display for VmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VmError::InvalidOpcode { ip, opcode } => {
+                write!(f, "invalid opcode {opcode} at ip {ip}")
+            }
+            VmError::InvalidOperand { ip, operand } => {
+                write!(f, "invalid operand {operand} at ip {ip}")
+            }
+            VmError::Overflow { ip, exponent } => {
+                write!(f, "2^{exponent} overflows at ip {ip}")
+            }
+            VmError::StepLimitExceeded { limit } => {
+                write!(f, "the program did not halt within {limit} steps")
+            }
+        }
+    }
+}
+
+impl std::error::Error for VmError {}
+
+/// Why [`Vm::run`] stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    Halted,
+    Breakpoint(usize),
+}
+
+/// The computer of the puzzle, run one instruction at a time
+#[derive(Debug, Clone)]
+pub struct Vm<'a> {
+    program: &'a Program,
+    pub registers: Registers,
+    pub ip: usize,
+    pub outputs: Vec<usize>,
+    steps: usize,
+    step_limit: usize,
+    breakpoints: BTreeSet<usize>,
+    trace: Option<Vec<Step>>,
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(program: &'a Program, registers: Registers) -> Self {
+        Vm {
+            program,
+            registers,
+            ip: 0,
+            outputs: vec![],
+            steps: 0,
+            step_limit: DEFAULT_STEP_LIMIT,
+            breakpoints: BTreeSet::new(),
+            trace: None,
+        }
+    }
+
+    /// Record every step from now on, see [`Vm::trace`]
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(vec![]);
+        self
+    }
+
+    /// Fail with [`VmError::StepLimitExceeded`] rather than executing more than `limit` steps
+    pub fn with_step_limit(mut self, limit: usize) -> Self {
+        self.step_limit = limit;
+        self
+    }
+
+    /// Stop [`Vm::run`] before executing the instruction at `ip`
+    pub fn add_breakpoint(&mut self, ip: usize) {
+        self.breakpoints.insert(ip);
+    }
+
+    pub fn remove_breakpoint(&mut self, ip: usize) {
+        self.breakpoints.remove(&ip);
+    }
+
+    /// The program halts when the instruction pointer is past its last instruction
+    pub fn is_halted(&self) -> bool {
+        self.ip + 1 >= self.program.len()
+    }
+
+    /// The number of steps executed so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// The steps executed so far, empty unless the VM was created [`Vm::with_trace`]
+    pub fn trace(&self) -> &[Step] {
+        self.trace.as_deref().unwrap_or_default()
+    }
+
+    /// Execute the instruction at the instruction pointer, or return `None` if halted. The
+    /// state is left untouched when the instruction fails.
+    pub fn step(&mut self) -> Result<Option<Step>, VmError> {
+        if self.is_halted() {
+            return Ok(None);
+        }
+        if self.steps == self.step_limit {
+            return Err(VmError::StepLimitExceeded {
+                limit: self.step_limit,
+            });
+        }
+        let ip = self.ip;
+        let (opcode, operand) = (self.program[ip], self.program[ip + 1]);
+        if opcode > 7 {
+            return Err(VmError::InvalidOpcode { ip, opcode });
+        }
+        if operand > 7 {
+            return Err(VmError::InvalidOperand { ip, operand });
+        }
+        let instruction = Instruction::from(opcode);
+        let before = self.registers;
+        let outputs = self.outputs.len();
+        let mut registers = self.registers;
+        self.ip = compute(
+            instruction,
+            operand.into(),
+            &mut registers,
+            ip,
+            &mut self.outputs,
+        )?;
+        self.registers = registers;
+        self.steps += 1;
+        let step = Step {
+            ip,
+            instruction,
+            operand,
+            before,
+            after: self.registers,
+            output: self.outputs.get(outputs).copied(),
+        };
+        if let Some(trace) = &mut self.trace {
+            trace.push(step.clone());
+        }
+        Ok(Some(step))
+    }
+
+    /// Execute instructions until the program halts or reaches a breakpoint. The instruction
+    /// at the current instruction pointer always runs, so calling it again resumes after a
+    /// breakpoint.
+    pub fn run(&mut self) -> Result<Stop, VmError> {
+        if self.step()?.is_none() {
+            return Ok(Stop::Halted);
+        }
+        loop {
+            if self.is_halted() {
+                return Ok(Stop::Halted);
+            }
+            if self.breakpoints.contains(&self.ip) {
+                return Ok(Stop::Breakpoint(self.ip));
+            }
+            self.step()?;
+        }
+    }
+}
+
+/// One line per step
+pub fn trace_to_text(trace: &[Step]) -> String {
+    trace.iter().map(|step| format!("{step}\n")).collect()
+}
+
+/// An array with one object per step, `output` is `null` when the step outputs nothing
+pub fn trace_to_json(trace: &[Step]) -> String {
+    let objects = trace.iter().map(|step| {
+        let output = step.output.map_or("null".to_string(), |o| o.to_string());
+        format!(
+            "  {{\"ip\": {}, \"instruction\": \"{}\", \"operand\": {}, \"before\": [{}], \"after\": [{}], \"output\": {output}}}",
+            step.ip,
+            step.instruction,
+            step.operand,
+            step.before.iter().join(", "),
+            step.after.iter().join(", "),
+        )
+    });
+    format!("[\n{}\n]\n", objects.format(",\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_step() {
+        let program = vec![0, 1, 5, 4, 3, 0];
+        let mut vm = Vm::new(&program, [3, 0, 0]);
+        let step = vm.step().unwrap().unwrap();
+        assert_eq!(
+            step,
+            Step {
+                ip: 0,
+                instruction: Instruction::adv,
+                operand: 1,
+                before: [3, 0, 0],
+                after: [1, 0, 0],
+                output: None,
+            }
+        );
+        assert_eq!(vm.step().unwrap().unwrap().output, Some(1));
+        assert_eq!(vm.step().unwrap().unwrap().ip, 4);
+        assert_eq!(vm.ip, 0);
+        while vm.step().unwrap().is_some() {}
+        assert!(vm.is_halted());
+        assert_eq!(vm.outputs, vec![1, 0]);
+        assert_eq!(vm.trace(), []);
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let program = vec![0, 1, 5, 4, 3, 0];
+        let mut vm = Vm::new(&program, [6, 0, 0]);
+        vm.add_breakpoint(2);
+        assert_eq!(vm.run().unwrap(), Stop::Breakpoint(2));
+        assert_eq!((vm.registers, vm.outputs.clone()), ([3, 0, 0], vec![]));
+        assert_eq!(vm.run().unwrap(), Stop::Breakpoint(2));
+        assert_eq!((vm.registers, vm.outputs.clone()), ([1, 0, 0], vec![3]));
+        vm.remove_breakpoint(2);
+        assert_eq!(vm.run().unwrap(), Stop::Halted);
+        assert_eq!(vm.outputs, vec![3, 1, 0]);
+        assert_eq!(vm.run().unwrap(), Stop::Halted);
+    }
+
+    #[test]
+    fn test_trace() {
+        let program = vec![2, 4, 5, 5];
+        let mut vm = Vm::new(&program, [9, 0, 0]).with_trace();
+        vm.run().unwrap();
+        assert_eq!(
+            trace_to_text(vm.trace()),
+            "  0: bst A  A=9 B=0 C=0 -> A=9 B=1 C=0\n  \
+               2: out B  A=9 B=1 C=0 -> A=9 B=1 C=0  out 1\n"
+        );
+        assert_eq!(
+            trace_to_json(&vm.trace()[..1]),
+            indoc! {r#"
+            [
+              {"ip": 0, "instruction": "bst", "operand": 4, "before": [9, 0, 0], "after": [9, 1, 0], "output": null}
+            ]
+        "#}
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let run = |program: Vec<usize>, registers| {
+            let mut vm = Vm::new(&program, registers).with_step_limit(100);
+            (vm.run(), vm.registers, vm.ip)
+        };
+        assert_eq!(
+            run(vec![0, 1, 5, 7], [4, 0, 0]),
+            (
+                Err(VmError::InvalidOperand { ip: 2, operand: 7 }),
+                [2, 0, 0],
+                2
+            )
+        );
+        assert_eq!(
+            run(vec![2, 8], [4, 0, 0]).0,
+            Err(VmError::InvalidOperand { ip: 0, operand: 8 })
+        );
+        assert_eq!(
+            run(vec![9, 0], [4, 0, 0]).0,
+            Err(VmError::InvalidOpcode { ip: 0, opcode: 9 })
+        );
+        assert_eq!(
+            run(vec![6, 4], [64, 0, 0]).0,
+            Err(VmError::Overflow {
+                ip: 0,
+                exponent: 64
+            })
+        );
+        assert_eq!(
+            run(vec![7, 5], [64, usize::MAX, 0]).0,
+            Err(VmError::Overflow {
+                ip: 0,
+                exponent: usize::MAX
+            })
+        );
+        let (result, _, _) = run(vec![5, 4, 3, 0], [1, 0, 0]);
+        assert_eq!(result, Err(VmError::StepLimitExceeded { limit: 100 }));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "the program did not halt within 100 steps"
+        );
+        assert_eq!(run(vec![6, 4], [63, 0, 0]).0, Ok(Stop::Halted));
+    }
+}
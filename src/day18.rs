@@ -1,172 +1,342 @@
-use std::collections::HashSet;
-
+use anyhow::{bail, Result};
 use num::Complex;
 use pathfinding::prelude::dijkstra;
+use rustc_hash::FxHashSet as HashSet;
+
+use crate::grid::{self, Pos};
+use crate::params::{self, parse_param, unknown_param};
+use crate::parsers::{parse_at, split_once_at, ParseError};
+
+/// `true` for corrupted cells
+type Grid = grid::Grid<bool>;
 
-type Pos = Complex<isize>;
+/// The size of the memory space and the number of bytes fallen for part 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    pub size: isize,
+    pub bytes: usize,
+}
 
-pub fn parse_input(input: &str) -> Vec<(usize, Pos)> {
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            size: 71,
+            bytes: 1024,
+        }
+    }
+}
+
+impl params::Params for Params {
+    const DAY: u8 = 18;
+
+    fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        match name {
+            "size" => self.size = parse_param(name, value)?,
+            "bytes" => self.bytes = parse_param(name, value)?,
+            _ => unknown_param(name)?,
+        }
+        Ok(())
+    }
+}
+
+impl Params {
+    fn target(&self) -> Pos {
+        Complex::new(self.size - 1, self.size - 1)
+    }
+}
+
+/// The bytes in the order they fall, each of them inside of the memory of `params`
+pub fn parse_input(input: &str, params: &Params) -> Result<Vec<(usize, Pos)>> {
+    let size = params.size;
     input
         .lines()
         .enumerate()
         .map(|(i, l)| {
-            let (x, y) = l.split_once(",").unwrap();
-            let pos = Complex::new(x.parse().unwrap(), y.parse().unwrap());
-            (i, pos)
+            let (x, y) = split_once_at(input, l, ",")?;
+            let pos = Complex::new(parse_at(input, x)?, parse_at(input, y)?);
+            if !(0..size).contains(&pos.re) || !(0..size).contains(&pos.im) {
+                let message = format!("byte {x},{y} is outside of the {size}x{size} memory");
+                return Err(ParseError::at(input, l, message).into());
+            }
+            Ok((i, pos))
         })
         .collect()
 }
 
-fn build_grid(positions: &[(usize, Pos)], limit: usize) -> HashSet<Pos> {
-    positions
-        .iter()
-        .filter(|(i, _)| *i < limit)
-        .map(|(_, p)| *p)
+fn build_grid(positions: &[(usize, Pos)], limit: usize, x_max: isize, y_max: isize) -> Grid {
+    let mut grid = Grid::new(x_max + 1, y_max + 1, false);
+    for (_, p) in positions.iter().filter(|(i, _)| *i < limit) {
+        grid[p] = true;
+    }
+    grid
+}
+
+fn successors(p: &Pos, grid: &Grid) -> Vec<(Pos, usize)> {
+    grid.neighbors(*p)
+        .filter(|next| !grid[next])
+        .map(|next| (next, 1))
         .collect()
 }
-const DIRECTIONS: [Pos; 4] = [
-    Complex::new(1, 0),
-    Complex::new(0, 1),
-    Complex::new(-1, 0),
-    Complex::new(0, -1),
-];
-
-fn successors(p: &Pos, grid: &HashSet<Pos>, x_max: isize, y_max: isize) -> Vec<(Pos, usize)> {
-    let mut next = Vec::new();
-    for d in DIRECTIONS {
-        match grid.get(&(p + d)) {
-            Some(_) => (),
-            None => {
-                if p.re >= 0 && p.re <= x_max && p.im >= 0 && p.im <= y_max {
-                    next.push((p + d, 1));
-                }
-            }
-        }
-    }
-    next
-}
-
-fn _print_grid(x_max: isize, y_max: isize, grid: &HashSet<Pos>, path: &Option<Vec<Pos>>) {
-    for j in 0..=y_max {
-        for i in 0..=x_max {
-            let pos = Complex::new(i, j);
-            if grid.contains(&pos) {
-                print!("#");
-            } else {
-                match path {
-                    None => print!("."),
-                    Some(p) => {
-                        if p.contains(&pos) {
-                            print!("O");
-                        } else {
-                            print!(".");
-                        }
-                    }
-                }
-            }
-        }
-        println!();
+
+fn _print_grid(grid: &Grid, path: &Option<Vec<Pos>>) {
+    let mut grid: grid::Grid<char> = grid
+        .iter()
+        .map(|(pos, corrupted)| (pos, if *corrupted { '#' } else { '.' }))
+        .collect();
+    for pos in path.iter().flatten() {
+        grid[pos] = 'O';
     }
-    println!();
+    println!("{grid}");
 }
 
-pub fn part1(positions: Vec<(usize, Pos)>) -> usize {
-    let limit = 1024;
-    let target = Complex::new(70, 70);
-    let grid = build_grid(&positions, limit);
-    let (x_max, y_max) = (target.re, target.im);
-    // _print_grid(x_max, y_max, &grid, &None);
+pub fn part1((positions, params): (Vec<(usize, Pos)>, Params)) -> usize {
+    let limit = params.bytes;
+    let target = params.target();
+    let grid = build_grid(&positions, limit, target.re, target.im);
+    // _print_grid(&grid, &None);
     if let Some((_path, cost)) = dijkstra(
         &Complex::new(0, 0),
-        |p| successors(p, &grid, x_max, y_max),
+        |p| successors(p, &grid),
         |p| *p == target,
     ) {
-        // _print_grid(x_max, y_max, &grid, &Some(_path));
+        // _print_grid(&grid, &Some(_path));
         cost
     } else {
         panic!("No solution found");
     }
 }
 
-pub fn part2(positions: Vec<(usize, Pos)>) -> Pos {
-    let mut max_steps = positions.len();
-    let mut min_steps = 1024;
-    let mut limit = (max_steps + min_steps) / 2;
-    let target = Complex::new(70, 70);
-    let (x_max, y_max) = (target.re, target.im);
-    let mut grid = build_grid(&positions, limit);
-
-    loop {
-        if dijkstra(
-            &Complex::new(0, 0),
-            |p| successors(p, &grid, x_max, y_max),
-            |p| *p == target,
-        )
-        .is_some()
-        {
-            min_steps = limit;
-            limit = (limit + max_steps) / 2;
-            if limit == min_steps {
-                break;
-            }
-            grid = build_grid(&positions, limit);
-        } else {
-            max_steps = limit;
-            let new_limit = (min_steps + limit + 1) / 2;
-            if new_limit == limit {
-                limit -= 1; // not sure why, but hey
-                break;
-            }
-            limit = new_limit;
-            grid = build_grid(&positions, limit);
+/// The first byte after which the exit cannot be reached, and how many cells are corrupted then
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cut {
+    /// The index of the byte in the input
+    pub index: usize,
+    pub byte: Pos,
+    pub corrupted: usize,
+}
+
+/// The sets of connected cells, merged as the cells are freed
+struct UnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        UnionFind {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.sizes[a] < self.sizes[b] {
+            (a, b) = (b, a);
         }
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+    }
+}
+
+/// Find the byte that cuts the exit off, by letting every byte fall then removing them, last
+/// first, until the start and the exit are connected again. Returns `None` if the exit can
+/// still be reached once every byte has fallen, and an error for a byte outside of the memory.
+pub fn first_cut(positions: &[(usize, Pos)], params: &Params) -> Result<Option<Cut>> {
+    let size = params.size;
+    let cell = |pos: Pos| (pos.im * size + pos.re) as usize;
+    let mut bytes = vec![0; (size * size) as usize];
+    for (index, pos) in positions {
+        if !(0..size).contains(&pos.re) || !(0..size).contains(&pos.im) {
+            bail!(
+                "byte {index} at {},{} is outside of the {size}x{size} memory",
+                pos.re,
+                pos.im
+            );
+        }
+        bytes[cell(*pos)] += 1;
+    }
+    let mut corrupted = bytes.iter().filter(|b| **b > 0).count();
+    let mut sets = UnionFind::new(bytes.len());
+    let grid = Grid::new(size, size, false);
+    let free = |pos: Pos, union: &mut UnionFind, bytes: &[usize]| {
+        for next in grid.neighbors(pos).filter(|next| bytes[cell(*next)] == 0) {
+            union.union(cell(pos), cell(next));
+        }
+    };
+    for pos in grid.positions().filter(|pos| bytes[cell(*pos)] == 0) {
+        free(pos, &mut sets, &bytes);
+    }
+    let (start, target) = (cell(Complex::new(0, 0)), cell(params.target()));
+    let connected = |sets: &mut UnionFind, bytes: &[usize]| {
+        bytes[start] == 0 && bytes[target] == 0 && sets.find(start) == sets.find(target)
+    };
+    if connected(&mut sets, &bytes) {
+        return Ok(None);
+    }
+    for (index, byte) in positions.iter().rev() {
+        bytes[cell(*byte)] -= 1;
+        if bytes[cell(*byte)] > 0 {
+            continue;
+        }
+        free(*byte, &mut sets, &bytes);
+        if connected(&mut sets, &bytes) {
+            return Ok(Some(Cut {
+                index: *index,
+                byte: *byte,
+                corrupted,
+            }));
+        }
+        corrupted -= 1;
+    }
+    Ok(None)
+}
+
+/// [`first_cut`] by bisecting on the number of fallen bytes, searching the exit each time
+pub fn bisect_cut(positions: &[(usize, Pos)], params: &Params) -> Option<Cut> {
+    let target = params.target();
+    let reachable = |fallen: usize| {
+        let grid = build_grid(positions, fallen, target.re, target.im);
+        !grid[&Complex::new(0, 0)]
+            && dijkstra(
+                &Complex::new(0, 0),
+                |p| successors(p, &grid),
+                |p| *p == target,
+            )
+            .is_some()
+    };
+    let counts: Vec<usize> = (0..=positions.len()).collect();
+    // the exit is reachable with fewer bytes than this, and not with this many
+    let fallen = counts.partition_point(|fallen| reachable(*fallen));
+    let (index, byte) = *positions.get(fallen.checked_sub(1)?)?;
+    let corrupted = positions[..fallen]
+        .iter()
+        .map(|(_, pos)| *pos)
+        .collect::<HashSet<Pos>>()
+        .len();
+    Some(Cut {
+        index,
+        byte,
+        corrupted,
+    })
+}
+
+pub fn part2((positions, params): (Vec<(usize, Pos)>, Params)) -> Pos {
+    match first_cut(&positions, &params) {
+        Ok(Some(cut)) => cut.byte,
+        Ok(None) => panic!("the exit is never cut off"),
+        Err(e) => panic!("{e}"),
     }
-    positions[limit].1
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use indoc::indoc;
     use pretty_assertions::assert_eq;
 
-    const INPUT: &str = indoc! {
-        "5,4
-        4,2
-        4,5
-        3,0
-        2,1
-        6,3
-        2,4
-        1,5
-        0,6
-        3,3
-        2,6
-        5,1
-        1,2
-        5,5
-        2,5
-        6,5
-        1,4
-        0,4
-        6,4
-        1,1
-        6,1
-        1,0
-        0,5
-        1,6
-        2,0"
-    };
+    const INPUT: &str = include_str!("../fixtures/day18.txt");
+
+    const PARAMS: Params = Params { size: 7, bytes: 12 };
 
     #[test]
     fn test_parse_input() {
-        let parsed = parse_input(INPUT);
-        assert_eq!(parsed[0], (0, Complex::new(5, 4)));
+        let positions = parse_input(INPUT, &PARAMS).unwrap();
+        assert_eq!(positions[0], (0, Complex::new(5, 4)));
     }
 
-    // #[test]
-    // fn test_part2() {
-    //     let parsed = parse_input(INPUT);
-    //     assert_eq!(part2(parsed), Complex::new(6, 1));
-    // }
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("5,4\n4;2", &PARAMS).unwrap_err();
+        assert_eq!(e.to_string(), "line 2, column 1: expected \",\" in \"4;2\"");
+        let e = parse_input("5,4\n4,y", &PARAMS).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 3: invalid value \"y\": invalid digit found in string"
+        );
+        for outside in ["7,0", "1,7", "-1,2"] {
+            let e = parse_input(&format!("5,4\n{outside}"), &PARAMS).unwrap_err();
+            assert_eq!(
+                e.to_string(),
+                format!("line 2, column 1: byte {outside} is outside of the 7x7 memory")
+            );
+        }
+        assert!(parse_input(INPUT, &Params::default()).is_ok());
+    }
+
+    #[test]
+    fn test_part1() {
+        let positions = parse_input(INPUT, &PARAMS).unwrap();
+        assert_eq!(part1((positions, PARAMS)), 22);
+    }
+
+    #[test]
+    fn test_part2() {
+        let positions = parse_input(INPUT, &PARAMS).unwrap();
+        assert_eq!(part2((positions, PARAMS)), Complex::new(6, 1));
+    }
+
+    #[test]
+    fn test_first_cut() {
+        let positions = parse_input(INPUT, &PARAMS).unwrap();
+        let cut = Cut {
+            index: 20,
+            byte: Complex::new(6, 1),
+            corrupted: 21,
+        };
+        assert_eq!(first_cut(&positions, &PARAMS).unwrap(), Some(cut));
+        assert_eq!(bisect_cut(&positions, &PARAMS), Some(cut));
+        assert_eq!(first_cut(&positions[..20], &PARAMS).unwrap(), None);
+        assert_eq!(bisect_cut(&positions[..20], &PARAMS), None);
+    }
+
+    #[test]
+    fn test_first_cut_outside() {
+        for (pos, outside) in [((8, 0), "8,0"), ((1, 7), "1,7"), ((-1, 2), "-1,2")] {
+            let positions = vec![(0, Complex::new(1, 1)), (1, Complex::new(pos.0, pos.1))];
+            let e = first_cut(&positions, &PARAMS).unwrap_err();
+            assert_eq!(
+                e.to_string(),
+                format!("byte 1 at {outside} is outside of the 7x7 memory")
+            );
+        }
+    }
+
+    #[test]
+    fn test_first_cut_is_bisect_cut() {
+        let mut seed: u64 = 2024;
+        let mut random = |max: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % max
+        };
+        for _ in 0..200 {
+            let size = 2 + random(10) as isize;
+            let params = Params { size, bytes: 0 };
+            // random cells, some of them more than once, the start and the exit included
+            let count = random((size * size) as usize * 3 / 2);
+            let positions: Vec<(usize, Pos)> = (0..count)
+                .map(|i| {
+                    let cell = random((size * size) as usize) as isize;
+                    (i, Complex::new(cell % size, cell / size))
+                })
+                .collect();
+            assert_eq!(
+                first_cut(&positions, &params).unwrap(),
+                bisect_cut(&positions, &params),
+                "{positions:?} in a grid of size {size}"
+            );
+        }
+    }
 }
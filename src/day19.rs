@@ -1,14 +1,22 @@
+use anyhow::Result;
 use memoize::memoize;
 use rustc_hash::FxHashSet as HashSet;
 
-pub fn parse_input(input: &str) -> (Vec<String>, Vec<String>) {
-    let (patterns, designs) = input.split_once("\n\n").unwrap();
+use crate::parsers::ParseError;
+
+pub fn parse_input(input: &str) -> Result<(Vec<String>, Vec<String>)> {
+    let (patterns, designs) = input.split_once("\n\n").ok_or_else(|| {
+        ParseError::at_end(
+            input,
+            "expected an empty line between the patterns and the designs",
+        )
+    })?;
     let mut patterns: Vec<String> = patterns.split(", ").map(String::from).collect();
     patterns.sort_by_key(|s| std::cmp::Reverse(s.len()));
 
     let designs = designs.lines().map(String::from).collect();
 
-    (patterns, designs)
+    Ok((patterns, designs))
 }
 
 #[memoize]
@@ -88,40 +96,37 @@ pub fn part2((patterns, designs): (Vec<String>, Vec<String>)) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use indoc::indoc;
     use pretty_assertions::assert_eq;
 
-    const INPUT: &str = indoc! {
-        "r, wr, b, g, bwu, rb, gb, br
-
-        brwrr
-        bggr
-        gbbr
-        rrbgbr
-        ubwu
-        bwurrg
-        brgr
-        bbrgwb"
-    };
+    const INPUT: &str = include_str!("../fixtures/day19.txt");
 
     #[test]
     fn test_parse_input() {
-        let (patterns, designs) = parse_input(INPUT);
+        let (patterns, designs) = parse_input(INPUT).unwrap();
         assert_eq!(patterns.len(), 8);
         assert_eq!(patterns[0], String::from("bwu"));
         assert_eq!(designs.len(), 8);
     }
 
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("r, wr, b\nbrwrr").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 6: expected an empty line between the patterns and the designs"
+        );
+    }
+
     #[test]
     fn test_part1() {
-        let (patterns, designs) = parse_input(INPUT);
+        let (patterns, designs) = parse_input(INPUT).unwrap();
         assert_eq!(part1((patterns, designs)), 6);
         // panic!();
     }
 
     #[test]
     fn test_part2() {
-        let (patterns, designs) = parse_input(INPUT);
+        let (patterns, designs) = parse_input(INPUT).unwrap();
         assert_eq!(part2((patterns, designs)), 16);
         // panic!();
     }
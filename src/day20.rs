@@ -1,62 +1,76 @@
-use num::Complex;
+use anyhow::Result;
 use pathfinding::prelude::dijkstra;
-use rustc_hash::FxHashSet as HashSet;
-
-type Pos = Complex<isize>;
-type Grid = HashSet<Pos>;
-
-pub fn parse_input(input: &str) -> (Grid, Pos, Pos) {
-    let mut start = Complex::default();
-    let mut end = Complex::default();
-    let mut grid = Grid::default();
-    for (j, l) in input.lines().enumerate() {
-        for (i, c) in l.chars().enumerate() {
-            let pos = Complex::new(i as isize, j as isize);
-            match c {
-                '#' => (),
-                '.' => {
-                    grid.insert(pos);
-                }
-                'S' => {
-                    start = pos;
-                    grid.insert(pos);
-                }
-                'E' => {
-                    end = pos;
-                    grid.insert(pos);
-                }
-                c => panic!("Unexpected character {c}"),
-            }
-        }
+
+use crate::grid::{self, Pos};
+use crate::params::{self, parse_param, unknown_param};
+use crate::parsers::ParseError;
+
+/// `true` for the track, `false` for walls
+type Grid = grid::DenseGrid<bool>;
+
+/// The minimum number of picoseconds a cheat must save to be counted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    pub min_saving: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params { min_saving: 100 }
     }
-    (grid, start, end)
 }
 
-const DIRECTIONS: [Pos; 4] = [
-    Complex::new(1, 0),
-    Complex::new(0, 1),
-    Complex::new(-1, 0),
-    Complex::new(0, -1),
-];
+impl params::Params for Params {
+    const DAY: u8 = 20;
 
-fn successors(pos: &Pos, grid: &Grid) -> Vec<(Pos, usize)> {
-    let mut next = Vec::new();
-    for d in DIRECTIONS {
-        if grid.contains(&(pos + d)) {
-            next.push((pos + d, 1))
+    fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        match name {
+            "min_saving" => self.min_saving = parse_param(name, value)?,
+            _ => unknown_param(name)?,
         }
+        Ok(())
     }
-    next
 }
 
-pub fn part1((grid, start, end): (Grid, Pos, Pos)) -> usize {
+pub fn parse_input(input: &str) -> Result<(Grid, Pos, Pos)> {
+    let mut start = None;
+    let mut end = None;
+    let grid = Grid::try_parse(input, |pos, c| match c {
+        '#' => Ok(false),
+        '.' => Ok(true),
+        'S' => {
+            start = Some(pos);
+            Ok(true)
+        }
+        'E' => {
+            end = Some(pos);
+            Ok(true)
+        }
+        c => Err(format!("unexpected character {c:?}")),
+    })?;
+    let start = start.ok_or_else(|| ParseError::at_end(input, "no start 'S' in the racetrack"))?;
+    let end = end.ok_or_else(|| ParseError::at_end(input, "no end 'E' in the racetrack"))?;
+    Ok((grid, start, end))
+}
+
+fn successors(pos: &Pos, grid: &Grid) -> Vec<(Pos, usize)> {
+    grid.neighbors(*pos)
+        .filter(|next| grid[next])
+        .map(|next| (next, 1))
+        .collect()
+}
+
+pub fn part1(((grid, start, end), params): ((Grid, Pos, Pos), Params)) -> usize {
     let (base_path, _) = dijkstra(&start, |p| successors(p, &grid), |p| *p == end).unwrap();
-    find_cheats(&base_path, 2, 100)
+    find_cheats(&base_path, 2, params.min_saving)
 }
 
 fn find_cheats(path: &[Pos], can_skip_length: usize, target_skip: usize) -> usize {
     let mut tot = 0;
-    for (idx, pos) in path[..path.len() - target_skip].iter().enumerate() {
+    for (idx, pos) in path[..path.len().saturating_sub(target_skip)]
+        .iter()
+        .enumerate()
+    {
         tot += path[idx + 1 + target_skip..]
             .iter()
             .enumerate()
@@ -72,54 +86,63 @@ fn find_cheats(path: &[Pos], can_skip_length: usize, target_skip: usize) -> usiz
     tot
 }
 
-pub fn part2((grid, start, end): (Grid, Pos, Pos)) -> usize {
+pub fn part2(((grid, start, end), params): ((Grid, Pos, Pos), Params)) -> usize {
     let (base_path, _) = dijkstra(&start, |p| successors(p, &grid), |p| *p == end).unwrap();
-    find_cheats(&base_path, 20, 100)
+    find_cheats(&base_path, 20, params.min_saving)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use indoc::indoc;
-    use num::Zero;
+    use num::{Complex, Zero};
     use pretty_assertions::assert_eq;
 
-    const INPUT: &str = indoc! {
-        "###############
-        #...#...#.....#
-        #.#.#.#.#.###.#
-        #S#...#.#.#...#
-        #######.#.#.###
-        #######.#.#...#
-        #######.#.###.#
-        ###..E#...#...#
-        ###.#######.###
-        #...###...#...#
-        #.#####.#.###.#
-        #.#...#.#.#...#
-        #.#.#.#.#.#.###
-        #...#...#...###
-        ###############"
-    };
+    const INPUT: &str = include_str!("../fixtures/day20.txt");
 
     #[test]
     fn test_parse_input() {
-        let (grid, start, end) = parse_input(INPUT);
+        let (grid, start, end) = parse_input(INPUT).unwrap();
         assert_eq!(start, Complex::new(1, 3));
         assert_eq!(end, Complex::new(5, 7));
-        assert_eq!(grid.get(&Complex::zero()), None);
-        assert_eq!(grid.get(&Complex::new(1, 1)), Some(&Complex::new(1, 1)));
-        assert_eq!(grid.get(&start), Some(&start));
-        assert_eq!(grid.get(&end), Some(&end));
+        assert!(!grid[&Complex::zero()]);
+        assert!(grid[&Complex::new(1, 1)]);
+        assert!(grid[&start]);
+        assert!(grid[&end]);
+    }
+
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("###\n#S#\n###").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 4: no end 'E' in the racetrack"
+        );
+        let e = parse_input("###\n#S#\n#x#").unwrap_err();
+        assert_eq!(e.to_string(), "line 3, column 2: unexpected character 'x'");
     }
 
     #[test]
     fn test_find_cheats() {
-        let (grid, start, end) = parse_input(INPUT);
+        let (grid, start, end) = parse_input(INPUT).unwrap();
         let (base_path, _) = dijkstra(&start, |p| successors(p, &grid), |p| *p == end).unwrap();
         let res = find_cheats(&base_path, 2, 2);
         assert_eq!(res, 44);
         let res_p2 = find_cheats(&base_path, 20, 50);
         assert_eq!(res_p2, 285);
     }
+
+    #[test]
+    fn test_part1() {
+        let (grid, start, end) = parse_input(INPUT).unwrap();
+        assert_eq!(part1(((grid.clone(), start, end), Params::default())), 0);
+        let params = Params { min_saving: 64 };
+        assert_eq!(part1(((grid, start, end), params)), 1);
+    }
+
+    #[test]
+    fn test_part2() {
+        let (grid, start, end) = parse_input(INPUT).unwrap();
+        let params = Params { min_saving: 76 };
+        assert_eq!(part2(((grid, start, end), params)), 3);
+    }
 }
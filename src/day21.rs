@@ -0,0 +1,194 @@
+use anyhow::Result;
+use rustc_hash::FxHashMap as HashMap;
+
+use crate::grid::{Grid, Pos};
+use crate::parsers::{parse_at, ParseError};
+
+const NUMERIC_KEYPAD: &str = "789\n456\n123\n#0A";
+const DIRECTIONAL_KEYPAD: &str = "#^A\n<v>";
+
+type Cache = HashMap<(char, char, usize), usize>;
+
+/// The keys to type, ending with `A`, and the numeric part of the code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Code {
+    pub keys: String,
+    pub value: usize,
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<Code>> {
+    input
+        .lines()
+        .map(|line| {
+            let digits = line
+                .strip_suffix('A')
+                .filter(|digits| !digits.is_empty())
+                .ok_or_else(|| ParseError::at(input, line, format!("invalid code {line:?}")))?;
+            if let Some((i, c)) = digits.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+                return Err(ParseError::at(input, &line[i..], format!("unknown key {c:?}")).into());
+            }
+            Ok(Code {
+                keys: line.to_string(),
+                value: parse_at(input, digits)?,
+            })
+        })
+        .collect()
+}
+
+struct Keypad {
+    keys: HashMap<char, Pos>,
+    gap: Pos,
+}
+
+impl Keypad {
+    fn new(layout: &str) -> Self {
+        let grid = Grid::parse(layout, |_, c| c);
+        let keys = grid.iter().map(|(pos, c)| (*c, pos)).collect();
+        let gap = grid.position(|c| *c == '#').unwrap();
+        Keypad { keys, gap }
+    }
+
+    /// The shortest ways to move from `from` to `to` and press it, avoiding the gap. Moving in
+    /// a straight line is always cheaper for the robot above, so we only try to do all the
+    /// horizontal moves first or all the vertical moves first.
+    fn paths(&self, from: char, to: char) -> Vec<String> {
+        let start = self.keys[&from];
+        let delta = self.keys[&to] - start;
+        let horizontal = if delta.re < 0 { "<" } else { ">" }.repeat(delta.re.unsigned_abs());
+        let vertical = if delta.im < 0 { "^" } else { "v" }.repeat(delta.im.unsigned_abs());
+        let mut paths = vec![];
+        if start + delta.re != self.gap {
+            paths.push(format!("{horizontal}{vertical}A"));
+        }
+        if start + Pos::new(0, delta.im) != self.gap {
+            paths.push(format!("{vertical}{horizontal}A"));
+        }
+        paths.dedup();
+        paths
+    }
+}
+
+/// Number of presses on the human keypad needed to type `sequence` on a directional keypad with
+/// `depth` robots in between
+fn sequence_cost(sequence: &str, depth: usize, keypad: &Keypad, cache: &mut Cache) -> usize {
+    if depth == 0 {
+        return sequence.len();
+    }
+    let mut previous = 'A';
+    let mut cost = 0;
+    for key in sequence.chars() {
+        cost += move_cost(previous, key, depth, keypad, cache);
+        previous = key;
+    }
+    cost
+}
+
+fn move_cost(from: char, to: char, depth: usize, keypad: &Keypad, cache: &mut Cache) -> usize {
+    if let Some(cost) = cache.get(&(from, to, depth)) {
+        return *cost;
+    }
+    let cost = keypad
+        .paths(from, to)
+        .iter()
+        .map(|path| sequence_cost(path, depth - 1, keypad, cache))
+        .min()
+        .unwrap();
+    cache.insert((from, to, depth), cost);
+    cost
+}
+
+fn complexities(codes: &[Code], robots: usize) -> usize {
+    let numeric = Keypad::new(NUMERIC_KEYPAD);
+    let directional = Keypad::new(DIRECTIONAL_KEYPAD);
+    let mut cache = Cache::default();
+    codes
+        .iter()
+        .map(|code| {
+            let mut previous = 'A';
+            let mut length = 0;
+            for key in code.keys.chars() {
+                length += numeric
+                    .paths(previous, key)
+                    .iter()
+                    .map(|path| sequence_cost(path, robots, &directional, &mut cache))
+                    .min()
+                    .unwrap();
+                previous = key;
+            }
+            length * code.value
+        })
+        .sum()
+}
+
+pub fn part1(codes: Vec<Code>) -> usize {
+    complexities(&codes, 2)
+}
+
+pub fn part2(codes: Vec<Code>) -> usize {
+    complexities(&codes, 25)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const INPUT: &str = include_str!("../fixtures/day21.txt");
+
+    #[test]
+    fn test_parse_input() {
+        let codes = parse_input(INPUT).unwrap();
+        assert_eq!(codes.len(), 5);
+        assert_eq!(
+            codes[0],
+            Code {
+                keys: "029A".to_string(),
+                value: 29
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("029A\n98B0A").unwrap_err();
+        assert_eq!(e.to_string(), "line 2, column 3: unknown key 'B'");
+        let e = parse_input("029A\n980").unwrap_err();
+        assert_eq!(e.to_string(), "line 2, column 1: invalid code \"980\"");
+        let e = parse_input("029A\nA").unwrap_err();
+        assert_eq!(e.to_string(), "line 2, column 1: invalid code \"A\"");
+    }
+
+    #[test]
+    fn test_paths() {
+        let numeric = Keypad::new(NUMERIC_KEYPAD);
+        assert_eq!(numeric.paths('A', '0'), vec!["<A"]);
+        assert_eq!(numeric.paths('A', '1'), vec!["^<<A"]);
+        assert_eq!(numeric.paths('2', '9'), vec![">^^A", "^^>A"]);
+        let directional = Keypad::new(DIRECTIONAL_KEYPAD);
+        assert_eq!(directional.paths('<', 'A'), vec![">>^A"]);
+        assert_eq!(directional.paths('A', 'A'), vec!["A"]);
+    }
+
+    #[test]
+    fn test_sequence_cost() {
+        let directional = Keypad::new(DIRECTIONAL_KEYPAD);
+        let mut cache = Cache::default();
+        // <A^A>^^AvvvA, typed on the first robot keypad for 029A
+        assert_eq!(
+            sequence_cost("<A^A>^^AvvvA", 2, &directional, &mut cache),
+            68
+        );
+    }
+
+    #[test]
+    fn test_part1() {
+        let codes = parse_input(INPUT).unwrap();
+        assert_eq!(part1(codes), 126384);
+    }
+
+    #[test]
+    fn test_part2() {
+        let codes = parse_input(INPUT).unwrap();
+        assert_eq!(part2(codes), 154115708116294);
+    }
+}
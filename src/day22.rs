@@ -0,0 +1,98 @@
+use anyhow::Result;
+
+use crate::parsers::parse_at;
+
+const PRUNE: usize = 16777216;
+const STEPS: usize = 2000;
+/// Each price change is in -9..=9, so a sequence of 4 changes is a number in base 19
+const SEQUENCES: usize = 19 * 19 * 19 * 19;
+
+pub fn parse_input(input: &str) -> Result<Vec<usize>> {
+    Ok(input
+        .lines()
+        .map(|line| parse_at(input, line))
+        .collect::<Result<_, _>>()?)
+}
+
+fn next_secret(secret: usize) -> usize {
+    let secret = (secret ^ (secret * 64)) % PRUNE;
+    let secret = (secret ^ (secret / 32)) % PRUNE;
+    (secret ^ (secret * 2048)) % PRUNE
+}
+
+pub fn part1(secrets: Vec<usize>) -> usize {
+    secrets
+        .into_iter()
+        .map(|secret| (0..STEPS).fold(secret, |secret, _| next_secret(secret)))
+        .sum()
+}
+
+pub fn part2(secrets: Vec<usize>) -> usize {
+    let mut bananas = vec![0; SEQUENCES];
+    // the last buyer who saw each sequence, as a buyer only sells the first time
+    let mut seen = vec![usize::MAX; SEQUENCES];
+    for (buyer, mut secret) in secrets.into_iter().enumerate() {
+        let mut sequence = 0;
+        let mut price = secret % 10;
+        for step in 0..STEPS {
+            secret = next_secret(secret);
+            let new_price = secret % 10;
+            sequence = (sequence * 19 + 9 + new_price - price) % SEQUENCES;
+            price = new_price;
+            if step >= 3 && seen[sequence] != buyer {
+                seen[sequence] = buyer;
+                bananas[sequence] += price;
+            }
+        }
+    }
+    bananas.into_iter().max().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const INPUT: &str = include_str!("../fixtures/day22.txt");
+
+    #[test]
+    fn test_parse_input() {
+        let secrets = parse_input(INPUT).unwrap();
+        assert_eq!(secrets, vec![1, 10, 100, 2024]);
+    }
+
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("1\n10\nabc").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 1: invalid value \"abc\": invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn test_next_secret() {
+        let secrets: Vec<usize> = std::iter::successors(Some(123), |s| Some(next_secret(*s)))
+            .skip(1)
+            .take(10)
+            .collect();
+        assert_eq!(
+            secrets,
+            vec![
+                15887950, 16495136, 527345, 704524, 1553684, 12683156, 11100544, 12249484, 7753432,
+                5908254
+            ]
+        );
+    }
+
+    #[test]
+    fn test_part1() {
+        let secrets = parse_input(INPUT).unwrap();
+        assert_eq!(part1(secrets), 37327623);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(vec![1, 2, 3, 2024]), 23);
+    }
+}
@@ -0,0 +1,86 @@
+use anyhow::Result;
+use itertools::Itertools;
+use pathfinding::prelude::maximal_cliques_collect;
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+
+use crate::parsers::split_once_at;
+
+type Network = HashMap<String, HashSet<String>>;
+
+pub fn parse_input(input: &str) -> Result<Network> {
+    let mut network = Network::default();
+    for line in input.lines() {
+        let (a, b) = split_once_at(input, line, "-")?;
+        network
+            .entry(a.to_string())
+            .or_default()
+            .insert(b.to_string());
+        network
+            .entry(b.to_string())
+            .or_default()
+            .insert(a.to_string());
+    }
+    Ok(network)
+}
+
+pub fn part1(network: Network) -> usize {
+    let mut triangles = HashSet::default();
+    for (a, neighbors) in network.iter().filter(|(a, _)| a.starts_with('t')) {
+        for (b, c) in neighbors.iter().tuple_combinations() {
+            if network[b].contains(c) {
+                let mut triangle = [a, b, c];
+                triangle.sort();
+                triangles.insert(triangle);
+            }
+        }
+    }
+    triangles.len()
+}
+
+pub fn part2(network: Network) -> String {
+    let cliques = maximal_cliques_collect(network.keys(), &mut |a, b| network[*a].contains(*b));
+    cliques
+        .into_iter()
+        .max_by_key(|clique| clique.len())
+        .map(|clique| clique.into_iter().sorted().join(","))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const INPUT: &str = include_str!("../fixtures/day23.txt");
+
+    #[test]
+    fn test_parse_input() {
+        let network = parse_input(INPUT).unwrap();
+        assert_eq!(network.len(), 16);
+        assert_eq!(
+            network["kh"].iter().sorted().collect::<Vec<_>>(),
+            vec!["qp", "ta", "tc", "ub"]
+        );
+    }
+
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("kh-tc\nqp kh").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 1: expected \"-\" in \"qp kh\""
+        );
+    }
+
+    #[test]
+    fn test_part1() {
+        let network = parse_input(INPUT).unwrap();
+        assert_eq!(part1(network), 7);
+    }
+
+    #[test]
+    fn test_part2() {
+        let network = parse_input(INPUT).unwrap();
+        assert_eq!(part2(network), "co,de,ka,ta");
+    }
+}
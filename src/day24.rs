@@ -0,0 +1,267 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use itertools::Itertools;
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+
+use crate::parsers::{parse_at, split_once_at, ParseError};
+
+type Wires = HashMap<String, bool>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    And,
+    Or,
+    Xor,
+}
+
+impl FromStr for Operation {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "AND" => Ok(Operation::And),
+            "OR" => Ok(Operation::Or),
+            "XOR" => Ok(Operation::Xor),
+            _ => Err(ParseError::new(1, 1, format!("unknown gate {s:?}"))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gate {
+    left: String,
+    operation: Operation,
+    right: String,
+    output: String,
+}
+
+impl Gate {
+    fn has_input(&self, prefix: char) -> bool {
+        self.left.starts_with(prefix) || self.right.starts_with(prefix)
+    }
+
+    fn is_first_bit(&self) -> bool {
+        self.left.ends_with("00") && self.right.ends_with("00")
+    }
+}
+
+fn parse_gate(input: &str, line: &str) -> Result<Gate, ParseError> {
+    let (gate, output) = split_once_at(input, line, " -> ")?;
+    let Some((left, operation, right)) = gate.split(' ').collect_tuple() else {
+        return Err(ParseError::at(
+            input,
+            line,
+            format!("invalid gate {gate:?}"),
+        ));
+    };
+    Ok(Gate {
+        left: left.to_string(),
+        operation: operation
+            .parse()
+            .map_err(|e: ParseError| e.rebase(input, operation))?,
+        right: right.to_string(),
+        output: output.to_string(),
+    })
+}
+
+pub fn parse_input(input: &str) -> Result<(Wires, Vec<Gate>)> {
+    let (wires, gates) = input.split_once("\n\n").ok_or_else(|| {
+        ParseError::at_end(
+            input,
+            "expected an empty line between the wires and the gates",
+        )
+    })?;
+    let wires = wires
+        .lines()
+        .map(|line| {
+            let (name, value) = split_once_at(input, line, ": ")?;
+            let value: u8 = parse_at(input, value)?;
+            Ok((name.to_string(), value == 1))
+        })
+        .collect::<Result<_, ParseError>>()?;
+    let gates = gates
+        .lines()
+        .map(|line| parse_gate(input, line))
+        .collect::<Result<_, _>>()?;
+    Ok((wires, gates))
+}
+
+/// Propagate the values through the gates until no gate can be computed anymore
+fn simulate(mut wires: Wires, gates: &[Gate]) -> Wires {
+    let mut remaining: Vec<&Gate> = gates.iter().collect();
+    while !remaining.is_empty() {
+        let before = remaining.len();
+        remaining.retain(|gate| {
+            let (Some(&left), Some(&right)) = (wires.get(&gate.left), wires.get(&gate.right))
+            else {
+                return true;
+            };
+            let value = match gate.operation {
+                Operation::And => left & right,
+                Operation::Or => left | right,
+                Operation::Xor => left ^ right,
+            };
+            wires.insert(gate.output.clone(), value);
+            false
+        });
+        if remaining.len() == before {
+            break;
+        }
+    }
+    wires
+}
+
+pub fn part1((wires, gates): (Wires, Vec<Gate>)) -> usize {
+    let wires = simulate(wires, &gates);
+    wires
+        .iter()
+        .filter(|(name, _)| name.starts_with('z'))
+        .sorted()
+        .rev()
+        .fold(0, |acc, (_, &value)| acc * 2 + value as usize)
+}
+
+/// The gates should form a ripple carry adder, where for each bit `i`
+///     x_i XOR y_i -> a_i,  a_i XOR carry_i -> z_i
+///     x_i AND y_i -> b_i,  a_i AND carry_i -> c_i,  b_i OR c_i -> carry_i+1
+/// so we look for the outputs that do not fit this structure.
+pub fn part2((_, gates): (Wires, Vec<Gate>)) -> String {
+    let last_z = gates
+        .iter()
+        .map(|gate| &gate.output)
+        .filter(|output| output.starts_with('z'))
+        .max()
+        .cloned()
+        .unwrap_or_default();
+    let feeds = |output: &str, operation: Operation| {
+        gates.iter().any(|gate| {
+            gate.operation == operation && (gate.left == output || gate.right == output)
+        })
+    };
+    let mut wrong = HashSet::default();
+    for gate in &gates {
+        let from_inputs = gate.has_input('x') || gate.has_input('y');
+        let is_wrong = match gate.operation {
+            // checked before the z outputs, so that a_i sent to z_i is caught too
+            Operation::Xor if from_inputs && !gate.is_first_bit() => {
+                !feeds(&gate.output, Operation::Xor)
+            }
+            _ if gate.output.starts_with('z') && gate.output != last_z => {
+                gate.operation != Operation::Xor
+            }
+            Operation::Xor if !from_inputs => !gate.output.starts_with('z'),
+            Operation::Xor => false,
+            Operation::And => !gate.is_first_bit() && !feeds(&gate.output, Operation::Or),
+            Operation::Or => false,
+        };
+        if is_wrong {
+            wrong.insert(gate.output.as_str());
+        }
+    }
+    wrong.into_iter().sorted().join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const INPUT: &str = include_str!("../fixtures/day24.txt");
+
+    const INPUT_LARGE: &str = include_str!("../fixtures/day24_large.txt");
+
+    /// A ripple carry adder on `bits` bits, with the outputs of some gates swapped
+    fn adder(bits: usize, swaps: &[(&str, &str)]) -> Vec<Gate> {
+        let gate = |left: String, operation, right: String, output: String| {
+            let output = swaps
+                .iter()
+                .find_map(|(a, b)| match output.as_str() {
+                    o if o == *a => Some(b.to_string()),
+                    o if o == *b => Some(a.to_string()),
+                    _ => None,
+                })
+                .unwrap_or(output);
+            Gate {
+                left,
+                operation,
+                right,
+                output,
+            }
+        };
+        let mut gates = vec![
+            gate("x00".into(), Operation::Xor, "y00".into(), "z00".into()),
+            gate("x00".into(), Operation::And, "y00".into(), "c00".into()),
+        ];
+        for i in 1..bits {
+            let carry = if i == bits - 1 {
+                format!("z{bits:02}")
+            } else {
+                format!("c{i:02}")
+            };
+            let (x, y) = (format!("x{i:02}"), format!("y{i:02}"));
+            let previous = format!("c{:02}", i - 1);
+            gates.extend([
+                gate(x.clone(), Operation::Xor, y.clone(), format!("a{i:02}")),
+                gate(x, Operation::And, y, format!("b{i:02}")),
+                gate(
+                    format!("a{i:02}"),
+                    Operation::Xor,
+                    previous.clone(),
+                    format!("z{i:02}"),
+                ),
+                gate(
+                    format!("a{i:02}"),
+                    Operation::And,
+                    previous,
+                    format!("d{i:02}"),
+                ),
+                gate(format!("b{i:02}"), Operation::Or, format!("d{i:02}"), carry),
+            ]);
+        }
+        gates
+    }
+
+    #[test]
+    fn test_parse_input() {
+        let (wires, gates) = parse_input(INPUT).unwrap();
+        assert_eq!(wires.len(), 6);
+        assert_eq!(wires["x00"], true);
+        assert_eq!(wires["y00"], false);
+        assert_eq!(
+            gates[1],
+            Gate {
+                left: "x01".into(),
+                operation: Operation::Xor,
+                right: "y01".into(),
+                output: "z01".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("x00: 1\n\nx00 NAND y00 -> z00").unwrap_err();
+        assert_eq!(e.to_string(), "line 3, column 5: unknown gate \"NAND\"");
+        let e = parse_input("x00: 1\n\nx00 AND y00 => z00").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 1: expected \" -> \" in \"x00 AND y00 => z00\""
+        );
+    }
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(parse_input(INPUT).unwrap()), 4);
+        assert_eq!(part1(parse_input(INPUT_LARGE).unwrap()), 2024);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2((Wires::default(), adder(5, &[]))), "");
+        let gates = adder(5, &[("a01", "b01"), ("z03", "d03")]);
+        assert_eq!(part2((Wires::default(), gates)), "a01,b01,d03,z03");
+        let gates = adder(3, &[("a01", "z01")]);
+        assert_eq!(part2((Wires::default(), gates)), "a01,z01");
+    }
+}
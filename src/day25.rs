@@ -0,0 +1,101 @@
+use anyhow::Result;
+
+use crate::parsers::ParseError;
+
+const HEIGHT: usize = 7;
+const WIDTH: usize = 5;
+
+type Heights = [usize; WIDTH];
+
+pub fn parse_input(input: &str) -> Result<(Vec<Heights>, Vec<Heights>)> {
+    let mut locks = vec![];
+    let mut keys = vec![];
+    for schematic in input.split("\n\n") {
+        let lines: Vec<&str> = schematic.lines().collect();
+        if lines.len() != HEIGHT {
+            return Err(ParseError::at(
+                input,
+                schematic,
+                format!("expected {HEIGHT} lines, found {}", lines.len()),
+            )
+            .into());
+        }
+        let mut heights = [0usize; WIDTH];
+        for line in &lines {
+            if line.len() != WIDTH {
+                return Err(ParseError::at(
+                    input,
+                    line,
+                    format!("expected {WIDTH} columns, found {}", line.len()),
+                )
+                .into());
+            }
+            for (i, c) in line.char_indices() {
+                match c {
+                    '#' => heights[i] += 1,
+                    '.' => {}
+                    c => {
+                        return Err(ParseError::at(
+                            input,
+                            &line[i..],
+                            format!("unexpected character {c:?}"),
+                        )
+                        .into())
+                    }
+                }
+            }
+        }
+        // the full row at the top or at the bottom is not part of the pins
+        let heights = heights.map(|h| h.saturating_sub(1));
+        if lines[0] == "#".repeat(WIDTH) {
+            locks.push(heights);
+        } else {
+            keys.push(heights);
+        }
+    }
+    Ok((locks, keys))
+}
+
+fn fits(lock: &Heights, key: &Heights) -> bool {
+    lock.iter().zip(key).all(|(l, k)| l + k <= HEIGHT - 2)
+}
+
+pub fn part1((locks, keys): (Vec<Heights>, Vec<Heights>)) -> usize {
+    locks
+        .iter()
+        .map(|lock| keys.iter().filter(|key| fits(lock, key)).count())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const INPUT: &str = include_str!("../fixtures/day25.txt");
+
+    #[test]
+    fn test_parse_input() {
+        let (locks, keys) = parse_input(INPUT).unwrap();
+        assert_eq!(locks, vec![[0, 5, 3, 4, 3], [1, 2, 0, 5, 3]]);
+        assert_eq!(
+            keys,
+            vec![[5, 0, 2, 1, 3], [4, 3, 4, 0, 2], [3, 0, 2, 0, 1]]
+        );
+    }
+
+    #[test]
+    fn test_parse_input_error() {
+        let e = parse_input("#####\n.####\n.####\n.####\n.#.#.\n.#...\n.....\n\n#####\n.#o##")
+            .unwrap_err();
+        assert_eq!(e.to_string(), "line 9, column 1: expected 7 lines, found 2");
+        let e = parse_input("#####\n.####\n.####\n.####\n.#.#.\n.#.o.\n.....").unwrap_err();
+        assert_eq!(e.to_string(), "line 6, column 4: unexpected character 'o'");
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = parse_input(INPUT).unwrap();
+        assert_eq!(part1(input), 3);
+    }
+}
@@ -0,0 +1,668 @@
+use std::convert::Infallible;
+use std::fmt::Display;
+use std::ops::{Index, IndexMut};
+
+use num::Complex;
+use rustc_hash::FxHashMap as HashMap;
+
+use crate::parsers::ParseError;
+
+/// A position in a grid: `re` is the column, `im` is the row.
+///
+/// The imaginary axis points down, as in the puzzle inputs, so multiplying a direction by `i`
+/// rotates it clockwise.
+pub type Pos = Complex<isize>;
+
+/// The 4-connected neighborhood
+pub const DIRECTIONS: [Pos; 4] = [
+    Complex::new(1, 0),
+    Complex::new(0, 1),
+    Complex::new(-1, 0),
+    Complex::new(0, -1),
+];
+
+/// The 8-connected neighborhood, diagonals included
+pub const DIRECTIONS_8: [Pos; 8] = [
+    Complex::new(1, 0),
+    Complex::new(1, 1),
+    Complex::new(0, 1),
+    Complex::new(-1, 1),
+    Complex::new(-1, 0),
+    Complex::new(-1, -1),
+    Complex::new(0, -1),
+    Complex::new(1, -1),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: HashMap<Pos, T>,
+    width: isize,
+    height: isize,
+}
+
+impl<T> Grid<T> {
+    /// Build a grid from a block of text, mapping each character with `f`.
+    ///
+    /// Panics if the lines do not all have the same length.
+    pub fn parse(input: &str, mut f: impl FnMut(Pos, char) -> T) -> Self {
+        match Self::try_parse(input, |pos, c| Ok::<_, Infallible>(f(pos, c))) {
+            Ok(grid) => grid,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    /// Build a grid from a block of text, the errors of `f` are reported at the character
+    /// that caused them. Every cell within bounds is set, as the lines must all have the same
+    /// length.
+    pub fn try_parse<E: Display>(
+        input: &str,
+        mut f: impl FnMut(Pos, char) -> Result<T, E>,
+    ) -> Result<Self, ParseError> {
+        let mut cells = HashMap::default();
+        let mut width = 0;
+        let mut height = 0;
+        for (j, line) in input.lines().enumerate() {
+            let mut line_width = 0;
+            for (i, c) in line.chars().enumerate() {
+                let pos = Complex::new(i as isize, j as isize);
+                let value = f(pos, c).map_err(|e| ParseError::new(j + 1, i + 1, e.to_string()))?;
+                cells.insert(pos, value);
+                line_width += 1;
+            }
+            if j == 0 {
+                width = line_width;
+            }
+            if line_width != width {
+                return Err(ParseError::new(
+                    j + 1,
+                    line_width.min(width) as usize + 1,
+                    format!("expected a line of width {width}, found {line_width}"),
+                ));
+            }
+            height = j as isize + 1;
+        }
+        Ok(Grid {
+            cells,
+            width,
+            height,
+        })
+    }
+
+    /// A `width` x `height` grid with every cell set to `value`
+    pub fn new(width: isize, height: isize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        let cells = (0..height)
+            .flat_map(|j| (0..width).map(move |i| Complex::new(i, j)))
+            .map(|pos| (pos, value.clone()))
+            .collect();
+        Grid {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> isize {
+        self.width
+    }
+
+    pub fn height(&self) -> isize {
+        self.height
+    }
+
+    /// Is the position within the bounds of the grid?
+    pub fn contains(&self, pos: &Pos) -> bool {
+        pos.re >= 0 && pos.re < self.width && pos.im >= 0 && pos.im < self.height
+    }
+
+    pub fn get(&self, pos: &Pos) -> Option<&T> {
+        self.cells.get(pos)
+    }
+
+    pub fn get_mut(&mut self, pos: &Pos) -> Option<&mut T> {
+        self.cells.get_mut(pos)
+    }
+
+    /// Set the value of a cell, returning the previous one.
+    ///
+    /// The bounds grow if the position is outside of them.
+    pub fn insert(&mut self, pos: Pos, value: T) -> Option<T> {
+        self.width = self.width.max(pos.re + 1);
+        self.height = self.height.max(pos.im + 1);
+        self.cells.insert(pos, value)
+    }
+
+    /// All the positions within bounds, row by row
+    pub fn positions(&self) -> impl Iterator<Item = Pos> + use<T> {
+        let width = self.width;
+        (0..self.height).flat_map(move |j| (0..width).map(move |i| Complex::new(i, j)))
+    }
+
+    /// The cells of the grid, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions()
+            .filter_map(|pos| self.cells.get(&pos).map(|v| (pos, v)))
+    }
+
+    /// The first position, row by row, whose value satisfies `predicate`
+    pub fn position(&self, predicate: impl Fn(&T) -> bool) -> Option<Pos> {
+        self.iter().find(|(_, v)| predicate(v)).map(|(pos, _)| pos)
+    }
+
+    /// The 4-connected neighbors of `pos` that are within bounds
+    pub fn neighbors(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        DIRECTIONS
+            .into_iter()
+            .map(move |d| pos + d)
+            .filter(|p| self.contains(p))
+    }
+
+    /// The 8-connected neighbors of `pos` that are within bounds
+    pub fn neighbors8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        DIRECTIONS_8
+            .into_iter()
+            .map(move |d| pos + d)
+            .filter(|p| self.contains(p))
+    }
+
+    /// The cells of row `j`, from left to right
+    pub fn row(&self, j: isize) -> impl Iterator<Item = (Pos, &T)> {
+        (0..self.width).filter_map(move |i| {
+            let pos = Complex::new(i, j);
+            self.cells.get(&pos).map(|v| (pos, v))
+        })
+    }
+
+    /// The cells of column `i`, from top to bottom
+    pub fn column(&self, i: isize) -> impl Iterator<Item = (Pos, &T)> {
+        (0..self.height).filter_map(move |j| {
+            let pos = Complex::new(i, j);
+            self.cells.get(&pos).map(|v| (pos, v))
+        })
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = (Pos, &T)>> {
+        (0..self.height).map(|j| self.row(j))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = (Pos, &T)>> {
+        (0..self.width).map(|i| self.column(i))
+    }
+}
+
+impl<T> FromIterator<(Pos, T)> for Grid<T> {
+    fn from_iter<I: IntoIterator<Item = (Pos, T)>>(iter: I) -> Self {
+        let mut grid = Grid {
+            cells: HashMap::default(),
+            width: 0,
+            height: 0,
+        };
+        for (pos, value) in iter {
+            grid.insert(pos, value);
+        }
+        grid
+    }
+}
+
+impl<T> Index<&Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: &Pos) -> &Self::Output {
+        &self.cells[pos]
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &Self::Output {
+        &self.cells[&pos]
+    }
+}
+
+impl<T> IndexMut<&Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: &Pos) -> &mut Self::Output {
+        self.cells
+            .get_mut(pos)
+            .expect("position outside of the grid")
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut Self::Output {
+        &mut self[&pos]
+    }
+}
+
+/// Render the grid row by row, missing cells are shown as spaces
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for j in 0..self.height {
+            for i in 0..self.width {
+                match self.cells.get(&Complex::new(i, j)) {
+                    Some(v) => write!(f, "{v}")?,
+                    None => write!(f, " ")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A grid stored row by row in a single `Vec`, for the days where lookups are the hot path.
+///
+/// Unlike [`Grid`], every cell within bounds holds a value and the bounds are fixed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DenseGrid<T> {
+    cells: Vec<T>,
+    width: isize,
+    height: isize,
+}
+
+impl<T> DenseGrid<T> {
+    /// Build a grid from a block of text, mapping each character with `f`.
+    ///
+    /// Panics if the lines do not all have the same length.
+    pub fn parse(input: &str, mut f: impl FnMut(Pos, char) -> T) -> Self {
+        match Self::try_parse(input, |pos, c| Ok::<_, Infallible>(f(pos, c))) {
+            Ok(grid) => grid,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    /// Build a grid from a block of text, the errors of `f` are reported at the character
+    /// that caused them
+    pub fn try_parse<E: Display>(
+        input: &str,
+        mut f: impl FnMut(Pos, char) -> Result<T, E>,
+    ) -> Result<Self, ParseError> {
+        let mut cells = Vec::with_capacity(input.len());
+        let mut width = 0;
+        let mut height = 0;
+        for (j, line) in input.lines().enumerate() {
+            let mut line_width = 0;
+            for (i, c) in line.chars().enumerate() {
+                let value = f(Complex::new(i as isize, j as isize), c)
+                    .map_err(|e| ParseError::new(j + 1, i + 1, e.to_string()))?;
+                cells.push(value);
+                line_width += 1;
+            }
+            if j == 0 {
+                width = line_width;
+            }
+            if line_width != width {
+                return Err(ParseError::new(
+                    j + 1,
+                    line_width.min(width) as usize + 1,
+                    format!("expected a line of width {width}, found {line_width}"),
+                ));
+            }
+            height += 1;
+        }
+        Ok(DenseGrid {
+            cells,
+            width,
+            height,
+        })
+    }
+
+    /// A `width` x `height` grid with every cell set to `value`
+    pub fn new(width: isize, height: isize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        DenseGrid {
+            cells: vec![value; (width * height) as usize],
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> isize {
+        self.width
+    }
+
+    pub fn height(&self) -> isize {
+        self.height
+    }
+
+    /// Is the position within the bounds of the grid?
+    pub fn contains(&self, pos: &Pos) -> bool {
+        pos.re >= 0 && pos.re < self.width && pos.im >= 0 && pos.im < self.height
+    }
+
+    fn index_of(&self, pos: &Pos) -> Option<usize> {
+        if self.contains(pos) {
+            Some((pos.im * self.width + pos.re) as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, pos: &Pos) -> Option<&T> {
+        self.index_of(pos).map(|idx| &self.cells[idx])
+    }
+
+    pub fn get_mut(&mut self, pos: &Pos) -> Option<&mut T> {
+        self.index_of(pos).map(|idx| &mut self.cells[idx])
+    }
+
+    /// All the positions within bounds, row by row
+    pub fn positions(&self) -> impl Iterator<Item = Pos> + use<T> {
+        let width = self.width;
+        (0..self.height).flat_map(move |j| (0..width).map(move |i| Complex::new(i, j)))
+    }
+
+    /// The cells of the grid, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// The first position, row by row, whose value satisfies `predicate`
+    pub fn position(&self, predicate: impl Fn(&T) -> bool) -> Option<Pos> {
+        self.iter().find(|(_, v)| predicate(v)).map(|(pos, _)| pos)
+    }
+
+    /// The 4-connected neighbors of `pos` that are within bounds
+    pub fn neighbors(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        DIRECTIONS
+            .into_iter()
+            .map(move |d| pos + d)
+            .filter(|p| self.contains(p))
+    }
+
+    /// The 8-connected neighbors of `pos` that are within bounds
+    pub fn neighbors8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        DIRECTIONS_8
+            .into_iter()
+            .map(move |d| pos + d)
+            .filter(|p| self.contains(p))
+    }
+
+    /// The cells of row `j`, from left to right
+    pub fn row(&self, j: isize) -> impl Iterator<Item = (Pos, &T)> {
+        let start = (j * self.width) as usize;
+        let cells = &self.cells[start..start + self.width as usize];
+        (0..self.width).map(move |i| Complex::new(i, j)).zip(cells)
+    }
+
+    /// The cells of column `i`, from top to bottom
+    pub fn column(&self, i: isize) -> impl Iterator<Item = (Pos, &T)> {
+        (0..self.height).map(move |j| (Complex::new(i, j), &self[Complex::new(i, j)]))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = (Pos, &T)>> {
+        (0..self.height).map(|j| self.row(j))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = (Pos, &T)>> {
+        (0..self.width).map(|i| self.column(i))
+    }
+}
+
+/// Collect cells into a grid, every position within the bounds must be given
+impl<T> FromIterator<(Pos, T)> for DenseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Pos, T)>>(iter: I) -> Self {
+        let cells: Vec<(Pos, T)> = iter.into_iter().collect();
+        let width = cells.iter().map(|(p, _)| p.re + 1).max().unwrap_or(0);
+        let height = cells.iter().map(|(p, _)| p.im + 1).max().unwrap_or(0);
+        let mut dense: Vec<Option<T>> = (0..width * height).map(|_| None).collect();
+        for (pos, value) in cells {
+            dense[(pos.im * width + pos.re) as usize] = Some(value);
+        }
+        DenseGrid {
+            cells: dense
+                .into_iter()
+                .map(|v| v.expect("missing cell in the grid"))
+                .collect(),
+            width,
+            height,
+        }
+    }
+}
+
+impl<T: Clone> From<&Grid<T>> for DenseGrid<T> {
+    fn from(grid: &Grid<T>) -> Self {
+        grid.iter().map(|(pos, v)| (pos, v.clone())).collect()
+    }
+}
+
+impl<T> Index<&Pos> for DenseGrid<T> {
+    type Output = T;
+
+    fn index(&self, pos: &Pos) -> &Self::Output {
+        self.get(pos).expect("position outside of the grid")
+    }
+}
+
+impl<T> Index<Pos> for DenseGrid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &Self::Output {
+        &self[&pos]
+    }
+}
+
+impl<T> IndexMut<&Pos> for DenseGrid<T> {
+    fn index_mut(&mut self, pos: &Pos) -> &mut Self::Output {
+        self.get_mut(pos).expect("position outside of the grid")
+    }
+}
+
+impl<T> IndexMut<Pos> for DenseGrid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut Self::Output {
+        &mut self[&pos]
+    }
+}
+
+impl<T: Display> Display for DenseGrid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for (_, v) in row {
+                write!(f, "{v}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    const INPUT: &str = indoc! {
+        "#..
+        .#.
+        ..#
+        ..."
+    };
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::parse(INPUT, |_, c| c == '#');
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 4);
+        assert!(grid[&Complex::new(0, 0)]);
+        assert!(!grid[&Complex::new(1, 0)]);
+        assert!(grid[Complex::new(2, 2)]);
+        assert_eq!(grid.get(&Complex::new(3, 0)), None);
+    }
+
+    #[test]
+    fn test_parse_gives_positions() {
+        let grid = Grid::parse(INPUT, |pos, _| pos);
+        assert!(grid.iter().all(|(pos, v)| pos == *v));
+        assert_eq!(grid.iter().count(), 12);
+    }
+
+    #[test]
+    fn test_contains() {
+        let grid = Grid::new(3, 2, 0);
+        assert!(grid.contains(&Complex::new(0, 0)));
+        assert!(grid.contains(&Complex::new(2, 1)));
+        assert!(!grid.contains(&Complex::new(3, 1)));
+        assert!(!grid.contains(&Complex::new(2, 2)));
+        assert!(!grid.contains(&Complex::new(-1, 0)));
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::new(3, 3, 0);
+        assert_eq!(grid.neighbors(Complex::new(1, 1)).count(), 4);
+        assert_eq!(
+            grid.neighbors(Complex::new(0, 0)).collect::<Vec<_>>(),
+            vec![Complex::new(1, 0), Complex::new(0, 1)]
+        );
+        assert_eq!(grid.neighbors8(Complex::new(1, 1)).count(), 8);
+        assert_eq!(grid.neighbors8(Complex::new(2, 2)).count(), 3);
+        assert_eq!(grid.neighbors8(Complex::new(1, 0)).count(), 5);
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = Grid::parse(INPUT, |_, c| c);
+        let rows: Vec<String> = grid
+            .rows()
+            .map(|row| row.map(|(_, c)| c).collect())
+            .collect();
+        assert_eq!(rows, vec!["#..", ".#.", "..#", "..."]);
+        let columns: Vec<String> = grid
+            .columns()
+            .map(|column| column.map(|(_, c)| c).collect())
+            .collect();
+        assert_eq!(columns, vec!["#...", ".#..", "..#."]);
+    }
+
+    #[test]
+    fn test_position() {
+        let grid = Grid::parse(INPUT, |_, c| c);
+        assert_eq!(grid.position(|c| *c == '#'), Some(Complex::new(0, 0)));
+        assert_eq!(grid.position(|c| *c == 'S'), None);
+    }
+
+    #[test]
+    fn test_from_iter_and_insert() {
+        let mut grid: Grid<char> =
+            Grid::from_iter([(Complex::new(0, 0), 'a'), (Complex::new(1, 1), 'b')]);
+        assert_eq!(grid.width(), 2);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid.insert(Complex::new(2, 0), 'c'), None);
+        assert_eq!(grid.width(), 3);
+        grid[Complex::new(0, 0)] = 'd';
+        assert_eq!(grid.to_string(), "d c\n b \n");
+    }
+
+    #[test]
+    fn test_display() {
+        let grid = Grid::parse(INPUT, |_, c| c);
+        assert_eq!(grid.to_string(), format!("{INPUT}\n"));
+    }
+
+    #[test]
+    fn test_dense_parse() {
+        let grid = DenseGrid::parse(INPUT, |_, c| c == '#');
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 4);
+        assert!(grid[&Complex::new(0, 0)]);
+        assert!(!grid[&Complex::new(1, 0)]);
+        assert!(grid[Complex::new(2, 2)]);
+        assert_eq!(grid.get(&Complex::new(3, 0)), None);
+        assert_eq!(grid.get(&Complex::new(-1, 0)), None);
+        assert_eq!(grid.get(&Complex::new(0, 4)), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_dense_parse_ragged() {
+        DenseGrid::parse("..\n.", |_, c| c);
+    }
+
+    #[test]
+    fn test_dense_iter_is_row_major() {
+        let grid = DenseGrid::parse(INPUT, |pos, _| pos);
+        assert!(grid.iter().all(|(pos, v)| pos == *v));
+        assert_eq!(grid.iter().count(), 12);
+        assert_eq!(grid.position(|p| p.im == 1), Some(Complex::new(0, 1)));
+    }
+
+    #[test]
+    fn test_dense_neighbors() {
+        let grid = DenseGrid::new(3, 3, 0);
+        assert_eq!(grid.neighbors(Complex::new(1, 1)).count(), 4);
+        assert_eq!(grid.neighbors(Complex::new(0, 0)).count(), 2);
+        assert_eq!(grid.neighbors8(Complex::new(1, 1)).count(), 8);
+        assert_eq!(grid.neighbors8(Complex::new(2, 2)).count(), 3);
+    }
+
+    #[test]
+    fn test_dense_rows_and_columns() {
+        let grid = DenseGrid::parse(INPUT, |_, c| c);
+        let rows: Vec<String> = grid
+            .rows()
+            .map(|row| row.map(|(_, c)| c).collect())
+            .collect();
+        assert_eq!(rows, vec!["#..", ".#.", "..#", "..."]);
+        let columns: Vec<String> = grid
+            .columns()
+            .map(|column| column.map(|(_, c)| c).collect())
+            .collect();
+        assert_eq!(columns, vec!["#...", ".#..", "..#."]);
+    }
+
+    #[test]
+    fn test_dense_from_grid() {
+        let grid = Grid::parse(INPUT, |_, c| c);
+        let mut dense = DenseGrid::from(&grid);
+        assert_eq!(dense.to_string(), grid.to_string());
+        dense[Complex::new(1, 0)] = '#';
+        assert_eq!(dense.row(0).map(|(_, c)| *c).collect::<String>(), "##.");
+    }
+
+    #[test]
+    fn test_try_parse() {
+        let grid = Grid::try_parse(INPUT, |_, c| match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            c => Err(format!("unexpected character {c:?}")),
+        })
+        .unwrap();
+        assert!(grid[Complex::new(1, 1)]);
+        let e = Grid::try_parse("..\n.x", |_, c| match c {
+            '.' => Ok(()),
+            c => Err(format!("unexpected character {c:?}")),
+        })
+        .unwrap_err();
+        assert_eq!(e, ParseError::new(2, 2, "unexpected character 'x'"));
+        let e = Grid::try_parse("...\n..\n...", |_, c| Ok::<_, String>(c)).unwrap_err();
+        assert_eq!(
+            e,
+            ParseError::new(2, 3, "expected a line of width 3, found 2")
+        );
+        let e = Grid::try_parse("..\n...", |_, c| Ok::<_, String>(c)).unwrap_err();
+        assert_eq!((e.line, e.column), (2, 3));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parse_ragged() {
+        Grid::parse("..\n.", |_, c| c);
+    }
+
+    #[test]
+    fn test_dense_try_parse() {
+        let e = DenseGrid::try_parse("...\n..\n...", |_, c| Ok::<_, String>(c)).unwrap_err();
+        assert_eq!((e.line, e.column), (2, 3));
+        let e = DenseGrid::try_parse("...\n..#", |_, c| match c {
+            '.' => Ok(c),
+            c => Err(format!("unexpected character {c:?}")),
+        })
+        .unwrap_err();
+        assert_eq!((e.line, e.column), (2, 3));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod bench;
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -18,4 +19,12 @@ pub mod day17;
 pub mod day18;
 pub mod day19;
 pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod grid;
+pub mod params;
 pub mod parsers;
+pub mod scaffold;
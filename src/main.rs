@@ -1,57 +1,281 @@
 use adventofcode2024::{
     day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12, day13,
-    day14, day15, day16, day17, day18, day19, day20,
+    day14, day15, day16, day17, day18, day19, day20, day21, day22, day23, day24, day25,
 };
 
-use anyhow::Result;
-use aoc_next::{aoc_main, failable_parser, parser, solution, solver, Aoc};
+use std::fs;
+use std::path::Path;
 
+use adventofcode2024::bench::{self, Bench};
+use adventofcode2024::params::{self, Override};
+use adventofcode2024::{params_parser, scaffold, tuned};
+use anyhow::{anyhow, bail, Result};
+use aoc_next::input::get_input;
+use aoc_next::{failable_parser, solution, solver, Aoc};
+
+/// The solutions are in [`SOLUTIONS`] rather than here, so that they can be benchmarked too
 const AOC: Aoc = Aoc {
     allow_download: true,
     year: 2024,
-    solutions: &[
-        solution! {1, failable_parser!{ day01::parse_input }, solver!{ day01::part1 }},
-        solution! {1, failable_parser!{ day01::parse_input }, solver!{ day01::part2 }},
-        solution! {2, parser!{ day02::parse_input }, solver!{ day02::part1 }},
-        solution! {2, parser!{ day02::parse_input }, solver!{ day02::part2 }},
-        solution! {3, parser!{ day03::parse_input }, solver!{ day03::part1 }},
-        solution! {3, parser!{ day03::parse_input_p2 }, solver!{ day03::part1 }},
-        solution! {4, parser!{ day04::parse_input }, solver!{ day04::part1 }},
-        solution! {4, parser!{ day04::parse_input_p2 }, solver!{ day04::part2 }},
-        solution! {5, parser!{ day05::parse_input }, solver!{ day05::part1 }},
-        solution! {5, parser!{ day05::parse_input }, solver!{ day05::part2 }},
-        solution! {6, parser!{ day06::parse_input }, solver!{ day06::part1 }},
-        solution! {6, parser!{ day06::parse_input }, solver!{ day06::part2 }},
-        solution! {7, parser!{ day07::parse_input }, solver!{ day07::part1 }},
-        solution! {7, parser!{ day07::parse_input }, solver!{ day07::part2 }},
-        solution! {8, parser!{ day08::parse_input }, solver!{ day08::part1 }},
-        solution! {8, parser!{ day08::parse_input }, solver!{ day08::part2 }},
-        solution! {9, parser!{ day09::parse_input }, solver!{ day09::part1 }},
-        solution! {9, parser!{ day09::parse_input }, solver!{ day09::part2 }},
-        solution! {10, parser!{ day10::parse_input }, solver!{ day10::part1 }},
-        solution! {10, parser!{ day10::parse_input }, solver!{ day10::part2 }},
-        solution! {11, parser!{ day11::parse_input }, solver!{ day11::part1 }},
-        solution! {11, parser!{ day11::parse_input }, solver!{ day11::part2 }},
-        solution! {12, parser!{ day12::parse_input }, solver!{ day12::part1 }},
-        solution! {13, parser!{ day13::parse_input }, solver!{ day13::part1 }},
-        solution! {13, parser!{ day13::parse_input }, solver!{ day13::part2 }},
-        solution! {14, parser!{ day14::parse_input }, solver!{ day14::part1 }},
-        solution! {14, parser!{ day14::parse_input }, solver!{ day14::part2 }},
-        solution! {15, parser!{ day15::parse_input }, solver!{ day15::part1 }},
-        solution! {15, parser!{ day15::parse_input_p2 }, solver!{ day15::part2 }},
-        solution! {16, parser!{ day16::parse_input }, solver!{ day16::part1 }},
-        solution! {16, parser!{ day16::parse_input }, solver!{ day16::part2 }},
-        solution! {18, parser!{ day18::parse_input }, solver!{ day18::part1 }},
-        solution! {18, parser!{ day18::parse_input }, solver!{ day18::part2 }},
-        solution! {19, parser!{ day19::parse_input }, solver!{ day19::part1 }},
-        solution! {19, parser!{ day19::parse_input }, solver!{ day19::part2 }},
-        solution! {17, parser!{ day17::parse_input }, solver!{ day17::part1 }},
-        solution! {17, parser!{ day17::parse_input }, solver!{ day17::part2 }},
-        solution! {20, parser!{ day20::parse_input }, solver!{ day20::part1 }},
-        solution! {20, parser!{ day20::parse_input }, solver!{ day20::part2 }},
-    ],
+    solutions: &[],
 };
 
+const SOLUTIONS: &[&dyn Bench] = &[
+    solution! {1, failable_parser!{ day01::parse_input }, solver!{ day01::part1 }},
+    solution! {1, failable_parser!{ day01::parse_input }, solver!{ day01::part2 }},
+    solution! {2, failable_parser!{ day02::parse_input }, solver!{ day02::part1 }},
+    solution! {2, failable_parser!{ day02::parse_input }, solver!{ day02::part2 }},
+    solution! {3, failable_parser!{ day03::parse_input }, solver!{ day03::part1 }},
+    solution! {3, failable_parser!{ day03::parse_input_p2 }, solver!{ day03::part1 }},
+    solution! {4, failable_parser!{ day04::parse_input }, solver!{ day04::part1 }},
+    solution! {4, failable_parser!{ day04::parse_input_p2 }, solver!{ day04::part2 }},
+    solution! {5, failable_parser!{ day05::parse_input }, solver!{ day05::part1 }},
+    solution! {5, failable_parser!{ day05::parse_input }, solver!{ day05::part2 }},
+    solution! {6, failable_parser!{ day06::parse_input }, solver!{ day06::part1 }},
+    solution! {6, failable_parser!{ day06::parse_input }, solver!{ day06::part2 }},
+    solution! {7, failable_parser!{ day07::parse_input }, solver!{ day07::part1 }},
+    solution! {7, failable_parser!{ day07::parse_input }, solver!{ day07::part2 }},
+    solution! {8, failable_parser!{ day08::parse_input }, solver!{ day08::part1 }},
+    solution! {8, failable_parser!{ day08::parse_input }, solver!{ day08::part2 }},
+    solution! {9, failable_parser!{ day09::parse_input }, solver!{ day09::part1 }},
+    solution! {9, failable_parser!{ day09::parse_input }, solver!{ day09::part2 }},
+    solution! {10, failable_parser!{ day10::parse_input }, solver!{ day10::part1 }},
+    solution! {10, failable_parser!{ day10::parse_input }, solver!{ day10::part2 }},
+    tuned! {11, failable_parser!{ day11::parse_input }, solver!{ day11::part1 }},
+    tuned! {11, failable_parser!{ day11::parse_input }, solver!{ day11::part2 }},
+    solution! {12, failable_parser!{ day12::parse_input }, solver!{ day12::part1 }},
+    solution! {12, failable_parser!{ day12::parse_input }, solver!{ day12::part2 }},
+    solution! {13, failable_parser!{ day13::parse_input }, solver!{ day13::part1 }},
+    solution! {13, failable_parser!{ day13::parse_input }, solver!{ day13::part2 }},
+    tuned! {14, failable_parser!{ day14::parse_input }, solver!{ day14::part1 }},
+    tuned! {14, failable_parser!{ day14::parse_input }, solver!{ day14::part2 }},
+    solution! {15, failable_parser!{ day15::parse_input }, solver!{ day15::part1 }},
+    solution! {15, failable_parser!{ day15::parse_input_p2 }, solver!{ day15::part2 }},
+    tuned! {16, failable_parser!{ day16::parse_input }, solver!{ day16::part1 }},
+    tuned! {16, failable_parser!{ day16::parse_input }, solver!{ day16::part2 }},
+    tuned! {18, params_parser!{ day18::parse_input }, solver!{ day18::part1 }},
+    tuned! {18, params_parser!{ day18::parse_input }, solver!{ day18::part2 }},
+    solution! {19, failable_parser!{ day19::parse_input }, solver!{ day19::part1 }},
+    solution! {19, failable_parser!{ day19::parse_input }, solver!{ day19::part2 }},
+    solution! {17, failable_parser!{ day17::parse_input }, solver!{ day17::part1 }},
+    solution! {17, failable_parser!{ day17::parse_input }, solver!{ day17::part2 }},
+    tuned! {20, failable_parser!{ day20::parse_input }, solver!{ day20::part1 }},
+    tuned! {20, failable_parser!{ day20::parse_input }, solver!{ day20::part2 }},
+    solution! {21, failable_parser!{ day21::parse_input }, solver!{ day21::part1 }},
+    solution! {21, failable_parser!{ day21::parse_input }, solver!{ day21::part2 }},
+    solution! {22, failable_parser!{ day22::parse_input }, solver!{ day22::part1 }},
+    solution! {22, failable_parser!{ day22::parse_input }, solver!{ day22::part2 }},
+    solution! {23, failable_parser!{ day23::parse_input }, solver!{ day23::part1 }},
+    solution! {23, failable_parser!{ day23::parse_input }, solver!{ day23::part2 }},
+    solution! {24, failable_parser!{ day24::parse_input }, solver!{ day24::part1 }},
+    solution! {24, failable_parser!{ day24::parse_input }, solver!{ day24::part2 }},
+    solution! {25, failable_parser!{ day25::parse_input }, solver!{ day25::part1 }},
+];
+
+const USAGE: &str = "usage: [day] [--param dayNN.name=value]... \
+    | bench [day] [--runs N] [--report FILE.json|FILE.csv] | scaffold <day> \
+    | disassemble [FILE] | replay <1|2> <DIR> [--ppm SCALE | --png SCALE]";
+
+/// Split the `--param dayNN.name=value` options from the other arguments
+fn parse_args(args: impl Iterator<Item = String>) -> Result<(Vec<String>, Vec<Override>)> {
+    let mut positional = vec![];
+    let mut overrides = vec![];
+    let mut args = args.peekable();
+    while let Some(arg) = args.next() {
+        if let Some(value) = arg.strip_prefix("--param=") {
+            overrides.push(value.parse()?);
+        } else if arg == "--param" {
+            let value = args
+                .next()
+                .ok_or_else(|| anyhow!("--param expects dayNN.name=value"))?;
+            overrides.push(value.parse()?);
+        } else {
+            positional.push(arg);
+        }
+    }
+    Ok((positional, overrides))
+}
+
+/// Run the solutions of `day`, or of every day, with `overrides` applied to their parameters
+fn run(day: Option<u8>, overrides: &[Override]) -> Result<()> {
+    println!("AOC {}", AOC.year);
+    for solution in SOLUTIONS {
+        if day.is_none_or(|day| solution.day() == day) {
+            let input = get_input(&AOC, solution.day())?;
+            match solution.bench(&input, 1, overrides) {
+                Ok(measure) => {
+                    println!(
+                        "Day {} - {} with {}: {}",
+                        measure.day, measure.solver, measure.parser, measure.answer
+                    );
+                    println!("\tparser: {:?}", measure.parse.min);
+                    println!("\tsolver: {:?}", measure.solve.min);
+                }
+                Err(e) => eprintln!("Day {} failed: {e:#}", solution.day()),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Time the solutions of `day`, or of every day, and write a report
+fn run_bench(args: &[String], overrides: &[Override]) -> Result<()> {
+    let mut day = None;
+    let mut runs = 10;
+    let mut report = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--runs" => runs = args.next().ok_or_else(|| anyhow!(USAGE))?.parse()?,
+            "--report" => report = Some(args.next().ok_or_else(|| anyhow!(USAGE))?),
+            day_arg => day = Some(day_arg.parse().map_err(|_| anyhow!(USAGE))?),
+        }
+    }
+
+    let mut measures = vec![];
+    for solution in SOLUTIONS {
+        if day.is_none_or(|day| solution.day() == day) {
+            let input = get_input(&AOC, solution.day())?;
+            let measure = solution.bench(&input, runs, overrides)?;
+            println!("{measure}");
+            measures.push(measure);
+        }
+    }
+    if let Some(path) = report {
+        let content = if path.ends_with(".csv") {
+            bench::to_csv(&measures)
+        } else {
+            bench::to_json(&measures)
+        };
+        fs::write(path, content)?;
+        println!("report written to {path}");
+    }
+    Ok(())
+}
+
+/// Print the day 17 program of `path`, or of the puzzle input, as assembly
+fn run_disassemble(path: Option<&String>) -> Result<()> {
+    let input = match path {
+        Some(path) => fs::read_to_string(path)?,
+        None => get_input(&AOC, 17)?,
+    };
+    let (_, program) = day17::parse_input(&input)?;
+    print!("{}", day17::disassemble(&program));
+    Ok(())
+}
+
+/// Write a frame of the day 15 warehouse after each move of the robot to `dir`
+fn run_replay(args: &[String]) -> Result<()> {
+    let [part, dir, options @ ..] = args else {
+        bail!(USAGE);
+    };
+    let format = match options {
+        [] => day15::FrameFormat::Text,
+        [option, scale] if option == "--ppm" => day15::FrameFormat::Ppm {
+            scale: scale.parse()?,
+        },
+        [option, scale] if option == "--png" => day15::FrameFormat::Png {
+            scale: scale.parse()?,
+        },
+        _ => bail!(USAGE),
+    };
+    let dir = Path::new(dir);
+    let count = match part.as_str() {
+        "1" => {
+            let (robot, grid, directions) = day15::parse_input(&get_input(&AOC, 15)?)?;
+            day15::export_frames(day15::Replay::new(robot, grid, directions), dir, format)?
+        }
+        "2" => {
+            let (robot, grid, directions) = day15::parse_input_p2(&get_input(&AOC, 15)?)?;
+            day15::export_frames(day15::Replay::new(robot, grid, directions), dir, format)?
+        }
+        _ => bail!(USAGE),
+    };
+    println!("{count} frames written to {}", dir.display());
+    Ok(())
+}
+
 pub fn main() -> Result<()> {
-    aoc_main(AOC)
+    let (args, overrides) = parse_args(std::env::args().skip(1))?;
+    params::check_overrides(&overrides)?;
+    match args.first().map(String::as_str) {
+        Some("bench") => run_bench(&args[1..], &overrides),
+        Some("scaffold") => {
+            let day = args
+                .get(1)
+                .ok_or_else(|| anyhow!("usage: scaffold <day>"))?
+                .parse()?;
+            scaffold::scaffold(Path::new(env!("CARGO_MANIFEST_DIR")), day)
+        }
+        Some("disassemble") => run_disassemble(args.get(1)),
+        Some("replay") => run_replay(&args[1..]),
+        Some(day) => {
+            let day = day.parse().map_err(|_| anyhow!(USAGE))?;
+            run(Some(day), &overrides)
+        }
+        None => run(None, &overrides),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeSet;
+
+    /// `dayNN::partN` for every solver in the `AOC` table
+    fn registered_solvers() -> BTreeSet<String> {
+        scaffold::registered_solvers(include_str!("main.rs"))
+            .into_iter()
+            .map(|(day, solver)| {
+                assert!(
+                    solver.starts_with(&scaffold::module_name(day)),
+                    "day {day} is registered with {solver}"
+                );
+                solver
+            })
+            .collect()
+    }
+
+    /// `dayNN::partN` for every `pub fn partN` in the day modules
+    fn defined_solvers() -> BTreeSet<String> {
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        fs::read_dir(src)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.is_file())
+            .filter_map(|path| {
+                let module = path.file_stem()?.to_str()?.to_string();
+                let is_day = module.len() == 5
+                    && module.starts_with("day")
+                    && module[3..].chars().all(|c| c.is_ascii_digit());
+                is_day.then_some((module, path))
+            })
+            .flat_map(|(module, path)| {
+                scaffold::defined_parts(&fs::read_to_string(path).unwrap())
+                    .into_iter()
+                    .map(move |part| format!("{module}::{part}"))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = ["14", "--param", "day14.width=11", "--param=day14.height=7"];
+        let (positional, overrides) = parse_args(args.into_iter().map(String::from)).unwrap();
+        assert_eq!(positional, vec!["14"]);
+        assert_eq!(
+            overrides,
+            vec![
+                "day14.width=11".parse().unwrap(),
+                "day14.height=7".parse::<Override>().unwrap()
+            ]
+        );
+        assert!(parse_args(["--param".to_string()].into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_all_parts_are_registered() {
+        assert_eq!(registered_solvers(), defined_solvers());
+    }
 }
@@ -0,0 +1,171 @@
+use std::slice;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::{day11, day14, day16, day18, day20};
+
+/// A parameter value given on the command line as `dayNN.name=value`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Override {
+    pub day: u8,
+    pub name: String,
+    pub value: String,
+}
+
+impl FromStr for Override {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("expected dayNN.name=value, found {s:?}"))?;
+        let (day, name) = key
+            .strip_prefix("day")
+            .and_then(|key| key.split_once('.'))
+            .ok_or_else(|| anyhow!("expected dayNN.name=value, found {s:?}"))?;
+        Ok(Override {
+            day: day
+                .parse()
+                .with_context(|| format!("invalid day in {s:?}"))?,
+            name: name.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+/// Check that every override sets a parameter that its day has to a valid value, so that a
+/// typo is reported before any puzzle is run
+pub fn check_overrides(overrides: &[Override]) -> Result<()> {
+    for o in overrides {
+        let o = slice::from_ref(o);
+        match o[0].day {
+            11 => day11::Params::with_overrides(o).map(drop),
+            14 => day14::Params::with_overrides(o).map(drop),
+            16 => day16::Params::with_overrides(o).map(drop),
+            18 => day18::Params::with_overrides(o).map(drop),
+            20 => day20::Params::with_overrides(o).map(drop),
+            day => Err(anyhow!("day{day:02} has no parameters")),
+        }?;
+    }
+    Ok(())
+}
+
+/// The tunable parameters of a puzzle, such as the size of its grid, that differ between the
+/// examples and the real input
+pub trait Params: Default {
+    const DAY: u8;
+
+    fn set(&mut self, name: &str, value: &str) -> Result<()>;
+
+    /// The defaults, with the overrides for this day applied
+    fn with_overrides(overrides: &[Override]) -> Result<Self> {
+        let mut params = Self::default();
+        for o in overrides.iter().filter(|o| o.day == Self::DAY) {
+            params
+                .set(&o.name, &o.value)
+                .with_context(|| format!("invalid parameter day{:02}.{}", o.day, o.name))?;
+        }
+        Ok(params)
+    }
+}
+
+/// Parse `value` for the parameter `name`
+pub fn parse_param<T>(name: &str, value: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    value
+        .parse()
+        .with_context(|| format!("invalid value {value:?} for {name}"))
+}
+
+/// The error for a parameter that the puzzle does not have
+pub fn unknown_param(name: &str) -> Result<()> {
+    bail!("unknown parameter {name:?}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Test {
+        width: usize,
+    }
+
+    impl Default for Test {
+        fn default() -> Self {
+            Test { width: 101 }
+        }
+    }
+
+    impl Params for Test {
+        const DAY: u8 = 14;
+
+        fn set(&mut self, name: &str, value: &str) -> Result<()> {
+            match name {
+                "width" => self.width = parse_param(name, value)?,
+                _ => unknown_param(name)?,
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_parse_override() {
+        let o: Override = "day14.width=11".parse().unwrap();
+        assert_eq!(
+            o,
+            Override {
+                day: 14,
+                name: "width".to_string(),
+                value: "11".to_string()
+            }
+        );
+        assert!("day14.width".parse::<Override>().is_err());
+        assert!("14.width=11".parse::<Override>().is_err());
+        assert!("dayx.width=11".parse::<Override>().is_err());
+    }
+
+    #[test]
+    fn test_with_overrides() {
+        assert_eq!(Test::with_overrides(&[]).unwrap(), Test { width: 101 });
+        let overrides = vec![
+            "day14.width=11".parse().unwrap(),
+            "day15.width=3".parse().unwrap(),
+        ];
+        assert_eq!(
+            Test::with_overrides(&overrides).unwrap(),
+            Test { width: 11 }
+        );
+
+        let e = Test::with_overrides(&["day14.height=7".parse().unwrap()]).unwrap_err();
+        assert_eq!(
+            format!("{e:#}"),
+            "invalid parameter day14.height: unknown parameter \"height\""
+        );
+        let e = Test::with_overrides(&["day14.width=x".parse().unwrap()]).unwrap_err();
+        assert_eq!(
+            format!("{e:#}"),
+            "invalid parameter day14.width: invalid value \"x\" for width: invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn test_check_overrides() {
+        let check = |o: &str| check_overrides(&[o.parse().unwrap()]).map_err(|e| format!("{e:#}"));
+        assert_eq!(check("day14.width=11"), Ok(()));
+        assert_eq!(
+            check("day99.foo=1"),
+            Err("day99 has no parameters".to_string())
+        );
+        assert_eq!(
+            check("day14.widht=11"),
+            Err("invalid parameter day14.widht: unknown parameter \"widht\"".to_string())
+        );
+        assert!(check("day18.size=x").is_err());
+    }
+}
@@ -1,14 +1,96 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
 use winnow::combinator::opt;
-use winnow::{ascii::digit1, PResult, Parser};
+use winnow::{ascii::digit1, ModalResult, Parser};
 
-pub fn num<T: std::str::FromStr>(input: &mut &str) -> PResult<T> {
+pub fn num<T: std::str::FromStr>(input: &mut &str) -> ModalResult<T> {
     digit1.parse_to().parse_next(input)
 }
 
-pub fn neg_num<T: std::str::FromStr>(input: &mut &str) -> PResult<T> {
+pub fn neg_num<T: std::str::FromStr>(input: &mut &str) -> ModalResult<T> {
     (opt('-'), digit1).take().parse_to().parse_next(input)
 }
 
+/// An error in a puzzle input, located by its line and column (both starting at 1)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// An error located at the start of `at`, which must be a slice of `input`
+    pub fn at(input: &str, at: &str, message: impl Into<String>) -> Self {
+        let offset = (at.as_ptr() as usize)
+            .saturating_sub(input.as_ptr() as usize)
+            .min(input.len());
+        let before = &input[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let column = before[line_start..].chars().count() + 1;
+        ParseError::new(line, column, message)
+    }
+
+    /// An error for an input that stops too early
+    pub fn at_end(input: &str, message: impl Into<String>) -> Self {
+        ParseError::at(input, &input[input.len()..], message)
+    }
+
+    /// Move an error found while parsing `s` on its own to its location in `input`,
+    /// `s` must be a slice of `input`
+    pub fn rebase(self, input: &str, s: &str) -> Self {
+        let origin = ParseError::at(input, s, "");
+        let column = if self.line == 1 {
+            origin.column + self.column - 1
+        } else {
+            self.column
+        };
+        ParseError::new(origin.line + self.line - 1, column, self.message)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parse `s`, a slice of `input`, reporting where it is in `input` if it fails
+pub fn parse_at<T>(input: &str, s: &str) -> Result<T, ParseError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    s.parse()
+        .map_err(|e| ParseError::at(input, s, format!("invalid value {s:?}: {e}")))
+}
+
+/// Split `s`, a slice of `input`, around the first `delimiter`
+pub fn split_once_at<'a>(
+    input: &str,
+    s: &'a str,
+    delimiter: &str,
+) -> Result<(&'a str, &'a str), ParseError> {
+    s.split_once(delimiter)
+        .ok_or_else(|| ParseError::at(input, s, format!("expected {delimiter:?} in {s:?}")))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -25,4 +107,38 @@ mod tests {
         assert_eq!(neg_num::<usize>.parse_next(&mut "123"), Ok(123usize));
         assert_eq!(neg_num::<isize>.parse_next(&mut "-123"), Ok(-123isize));
     }
+
+    #[test]
+    fn test_parse_error_at() {
+        let input = "12 34\n56 7x\n";
+        let e = ParseError::at(input, &input[9..], "bad");
+        assert_eq!(e, ParseError::new(2, 4, "bad"));
+        assert_eq!(e.to_string(), "line 2, column 4: bad");
+        assert_eq!(ParseError::at(input, input, "").line, 1);
+        assert_eq!(
+            ParseError::at_end(input, "truncated"),
+            ParseError::new(3, 1, "truncated")
+        );
+    }
+
+    #[test]
+    fn test_parse_error_rebase() {
+        let input = "abc\ndef ghi\njkl";
+        let s = &input[8..];
+        let e = ParseError::new(1, 2, "bad").rebase(input, s);
+        assert_eq!(e, ParseError::new(2, 6, "bad"));
+        let e = ParseError::new(2, 2, "bad").rebase(input, s);
+        assert_eq!(e, ParseError::new(3, 2, "bad"));
+    }
+
+    #[test]
+    fn test_parse_at() {
+        let input = "1,2\n3,x";
+        let (a, b) = split_once_at(input, &input[4..], ",").unwrap();
+        assert_eq!(parse_at::<usize>(input, a), Ok(3));
+        let e = parse_at::<usize>(input, b).unwrap_err();
+        assert_eq!((e.line, e.column), (2, 3));
+        let e = split_once_at(input, &input[4..], ";").unwrap_err();
+        assert_eq!((e.line, e.column), (2, 1));
+    }
 }
@@ -0,0 +1,321 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Result};
+use regex::Regex;
+
+const MAX_WIDTH: usize = 100;
+const USE_START: &str = "use adventofcode2024::{";
+const SOLUTIONS_START: &str = "const SOLUTIONS: &[&dyn Bench] = &[";
+
+pub fn module_name(day: u8) -> String {
+    format!("day{day:02}")
+}
+
+/// `(day, dayNN::partN)` for every solver in the solutions table of `main`
+pub fn registered_solvers(main: &str) -> Vec<(u8, String)> {
+    let solution =
+        Regex::new(r"(?:solution|tuned)!\s*\{\s*(\d+)\s*,.*solver!\s*\{\s*(day\d+::\w+)\s*\}\s*\}")
+            .unwrap();
+    solution
+        .captures_iter(main)
+        .map(|c| (c[1].parse().unwrap(), c[2].to_string()))
+        .collect()
+}
+
+/// The name of every `pub fn partN` in the source of a day module
+pub fn defined_parts(source: &str) -> Vec<String> {
+    let part = Regex::new(r"(?m)^pub fn (part\d+)\b").unwrap();
+    part.captures_iter(source)
+        .map(|c| c[1].to_string())
+        .collect()
+}
+
+/// Add `pub mod module;` to `lib`, keeping the modules sorted. Returns `None` if it is
+/// already there.
+pub fn add_module(lib: &str, module: &str) -> Option<String> {
+    let declaration = format!("pub mod {module};");
+    let mut lines: Vec<&str> = lib.lines().collect();
+    if lines.contains(&declaration.as_str()) {
+        return None;
+    }
+    let modules: Vec<usize> = (0..lines.len())
+        .filter(|&i| lines[i].starts_with("pub mod "))
+        .collect();
+    let index = modules
+        .iter()
+        .find(|&&i| lines[i] > declaration.as_str())
+        .copied()
+        .or(modules.last().map(|i| i + 1))
+        .unwrap_or(lines.len());
+    lines.insert(index, &declaration);
+    Some(lines.join("\n") + "\n")
+}
+
+/// Format a `use` list the way `rustfmt` does
+fn format_use(modules: &[String]) -> String {
+    let one_line = format!("{USE_START}{}}};", modules.join(", "));
+    if one_line.len() <= MAX_WIDTH {
+        return one_line;
+    }
+    let mut lines = vec![USE_START.to_string()];
+    let mut line = String::from("   ");
+    for module in modules {
+        if line.len() + module.len() + 2 > MAX_WIDTH {
+            lines.push(line);
+            line = String::from("   ");
+        }
+        line.push(' ');
+        line.push_str(module);
+        line.push(',');
+    }
+    lines.push(line);
+    lines.push(String::from("};"));
+    lines.join("\n")
+}
+
+/// Add `module` to the modules imported from the library in `main`
+fn add_use(main: &str, module: &str) -> Result<Option<String>> {
+    let start = main
+        .find(USE_START)
+        .ok_or_else(|| anyhow!("no {USE_START:?} in main.rs"))?;
+    let end = start
+        + main[start..]
+            .find("};")
+            .ok_or_else(|| anyhow!("unterminated {USE_START:?} in main.rs"))?
+        + 2;
+    let mut modules: Vec<String> = main[start + USE_START.len()..end - 2]
+        .split(',')
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty())
+        .collect();
+    if modules.iter().any(|m| m == module) {
+        return Ok(None);
+    }
+    modules.push(module.to_string());
+    modules.sort();
+    Ok(Some(format!(
+        "{}{}{}",
+        &main[..start],
+        format_use(&modules),
+        &main[end..]
+    )))
+}
+
+/// Register `parts` of `day` in the solutions table of `main`, after the existing solutions
+/// of that day or of the days before it
+fn add_solutions(main: &str, day: u8, parts: &[String]) -> Result<Option<String>> {
+    let module = module_name(day);
+    let registered = registered_solvers(main);
+    let missing: Vec<&String> = parts
+        .iter()
+        .filter(|part| !registered.contains(&(day, format!("{module}::{part}"))))
+        .collect();
+    if missing.is_empty() {
+        return Ok(None);
+    }
+    let start = main
+        .find(SOLUTIONS_START)
+        .ok_or_else(|| anyhow!("no solutions table in main.rs"))?;
+    let mut lines: Vec<String> = main.lines().map(String::from).collect();
+    let first = main[..start].matches('\n').count() + 1;
+    let end = first
+        + lines[first..]
+            .iter()
+            .position(|line| line.trim() == "];")
+            .ok_or_else(|| anyhow!("unterminated solutions table in main.rs"))?;
+    let index = (first..end)
+        .rev()
+        .find(|&i| {
+            registered_solvers(&lines[i])
+                .first()
+                .is_some_and(|(d, _)| *d <= day)
+        })
+        .map_or(first, |i| i + 1);
+    for (offset, part) in missing.into_iter().enumerate() {
+        let solution = format!(
+            "    solution! {{{day}, failable_parser!{{ {module}::parse_input }}, solver!{{ {module}::{part} }}}},"
+        );
+        lines.insert(index + offset, solution);
+    }
+    Ok(Some(lines.join("\n") + "\n"))
+}
+
+/// Create the module of `day` from the template in the crate at `root` and register it in
+/// `lib.rs` and `main.rs`. An existing day is never overwritten, only its missing parts are
+/// registered, so running it again after writing `part2` adds it to the solutions.
+pub fn scaffold(root: &Path, day: u8) -> Result<()> {
+    if !(1..=25).contains(&day) {
+        bail!("there is no day {day} in the calendar");
+    }
+    let module = module_name(day);
+    let src = root.join("src");
+    let path = src.join(format!("{module}.rs"));
+    let source = if path.exists() {
+        println!("{} already exists, leaving it untouched", path.display());
+        fs::read_to_string(&path)?
+    } else {
+        let template = fs::read_to_string(root.join("day_template.txt"))?;
+        fs::write(&path, &template)?;
+        println!("created {}", path.display());
+        template
+    };
+
+    let lib_path = src.join("lib.rs");
+    if let Some(lib) = add_module(&fs::read_to_string(&lib_path)?, &module) {
+        fs::write(&lib_path, lib)?;
+        println!("added {module} to {}", lib_path.display());
+    }
+
+    let main_path = src.join("main.rs");
+    let main = fs::read_to_string(&main_path)?;
+    let with_use = add_use(&main, &module)?;
+    let with_solutions = add_solutions(
+        with_use.as_deref().unwrap_or(&main),
+        day,
+        &defined_parts(&source),
+    )?;
+    if with_solutions.is_some() {
+        println!("registered the solutions of {module}");
+    }
+    if let Some(main) = with_solutions.or(with_use) {
+        fs::write(&main_path, main)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    const MAIN: &str = indoc! {
+        "use adventofcode2024::{day01, day03};
+
+        const SOLUTIONS: &[&dyn Bench] = &[
+            solution! {1, failable_parser!{ day01::parse_input }, solver!{ day01::part1 }},
+            solution! {3, failable_parser!{ day03::parse_input }, solver!{ day03::part1 }},
+        ];
+        "
+    };
+
+    #[test]
+    fn test_registered_solvers() {
+        assert_eq!(
+            registered_solvers(MAIN),
+            vec![
+                (1, "day01::part1".to_string()),
+                (3, "day03::part1".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_defined_parts() {
+        let source = "pub fn parse_input() {}\npub fn part1() {}\nfn part3() {}\npub fn part2() {}";
+        assert_eq!(defined_parts(source), vec!["part1", "part2"]);
+    }
+
+    #[test]
+    fn test_add_module() {
+        let lib = "pub mod day01;\npub mod day03;\npub mod grid;\n";
+        assert_eq!(
+            add_module(lib, "day02").unwrap(),
+            "pub mod day01;\npub mod day02;\npub mod day03;\npub mod grid;\n"
+        );
+        assert_eq!(add_module(lib, "day03"), None);
+        assert_eq!(
+            add_module("pub mod day01;\n", "day02").unwrap(),
+            "pub mod day01;\npub mod day02;\n"
+        );
+    }
+
+    #[test]
+    fn test_add_use() {
+        let main = add_use(MAIN, "day02").unwrap().unwrap();
+        assert!(main.starts_with("use adventofcode2024::{day01, day02, day03};\n"));
+        assert_eq!(add_use(&main, "day02").unwrap(), None);
+    }
+
+    #[test]
+    fn test_format_use_is_rustfmt_style() {
+        let main = include_str!("main.rs");
+        let start = main.find(USE_START).unwrap();
+        let end = start + main[start..].find("};").unwrap() + 2;
+        let modules: Vec<String> = (1..=25).map(module_name).collect();
+        assert_eq!(format_use(&modules), &main[start..end]);
+    }
+
+    #[test]
+    fn test_scaffold() {
+        let crate_root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let root = std::env::temp_dir().join(format!("scaffold_{}", std::process::id()));
+        let src = root.join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::copy(
+            crate_root.join("day_template.txt"),
+            root.join("day_template.txt"),
+        )
+        .unwrap();
+        // the crate as it was before day 25
+        let lib = fs::read_to_string(crate_root.join("src/lib.rs")).unwrap();
+        let main = fs::read_to_string(crate_root.join("src/main.rs")).unwrap();
+        let day25 = "    solution! {25, failable_parser!{ day25::parse_input }, solver!{ day25::part1 }},\n";
+        fs::write(src.join("lib.rs"), lib.replace("pub mod day25;\n", "")).unwrap();
+        fs::write(
+            src.join("main.rs"),
+            main.replace(" day25,", "").replace(day25, ""),
+        )
+        .unwrap();
+
+        scaffold(&root, 25).unwrap();
+        let read = |name: &str| fs::read_to_string(src.join(name)).unwrap();
+        assert_eq!(read("day25.rs"), include_str!("../day_template.txt"));
+        assert_eq!(read("lib.rs"), lib);
+        assert_eq!(read("main.rs"), main);
+
+        let modified = |name: &str| fs::metadata(src.join(name)).unwrap().modified().unwrap();
+        let before = ["day25.rs", "lib.rs", "main.rs"].map(modified);
+        scaffold(&root, 25).unwrap();
+        assert_eq!(["day25.rs", "lib.rs", "main.rs"].map(modified), before);
+        assert_eq!(read("main.rs"), main);
+
+        let source = "pub fn part1() {}\npub fn part2() {}\n";
+        fs::write(src.join("day25.rs"), source).unwrap();
+        scaffold(&root, 25).unwrap();
+        assert_eq!(read("day25.rs"), source);
+        assert_eq!(
+            registered_solvers(&read("main.rs")).last(),
+            Some(&(25, "day25::part2".to_string()))
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_add_solutions() {
+        let parts = vec!["part1".to_string(), "part2".to_string()];
+        let main = add_solutions(MAIN, 2, &parts).unwrap().unwrap();
+        assert_eq!(
+            registered_solvers(&main),
+            vec![
+                (1, "day01::part1".to_string()),
+                (2, "day02::part1".to_string()),
+                (2, "day02::part2".to_string()),
+                (3, "day03::part1".to_string())
+            ]
+        );
+        assert_eq!(add_solutions(&main, 2, &parts).unwrap(), None);
+
+        let main = add_solutions(MAIN, 1, &parts).unwrap().unwrap();
+        assert_eq!(
+            registered_solvers(&main)[..3],
+            [
+                (1, "day01::part1".to_string()),
+                (1, "day01::part2".to_string()),
+                (3, "day03::part1".to_string())
+            ]
+        );
+    }
+}